@@ -1,15 +1,37 @@
 //! Command line argument parsing and decision making.
 
+use crate::config::{init_config, show_config};
+use crate::error::{OnError, PadError};
+use crate::padrc::{allow_padrc, deny_padrc, TrustOpt};
 use crate::path::{
     add::{add_to_path, AddOpt},
+    apply::{apply_manifest, ApplyOpt},
+    batch::{apply_batch, BatchOpt},
     clean::{clean_path, CleanOpt},
+    convert::{convert_path, ConvertOpt},
+    diff::{diff_paths, DiffOpt},
+    edit::{edit_path, EditOpt},
+    exec::{exec_with_path, spawn_shell, ExecOpt, ShellOpt},
+    export::{export_path, ExportOpt},
+    find::{find_in_path, FindOpt},
+    list::{list_path, LsOpt},
     priority::{decrease_priority, increase_priority, MvOpt},
-    read::read_path,
+    read::read_raw_path,
     remove::{rm_from_path, RmOpt},
     revert::{revert_path, RevertOpt},
+    target::Target,
+    write::WriteOpt,
 };
+use crate::shell::{
+    hook::{run_hook, HookOpt},
+    import::{import_path, ImportOpt},
+    persist::{persist_path, PersistOpt},
+};
+use crate::system::{given::GivenPath, stdout, system, with_system};
+#[cfg(feature = "tui")]
+use crate::tui::{edit_interactively, TuiOpt};
 use clap::{crate_authors, crate_description, crate_name, Parser};
-use std::io;
+use std::ffi::OsString;
 
 /// Configuration for the entire application.
 ///
@@ -21,6 +43,19 @@ use std::io;
     about = crate_description!(),
 )]
 pub struct Opt {
+    /// What to do when a command fails
+    #[clap(long, value_enum, global = true, default_value_t)]
+    pub on_error: OnError,
+
+    /// `$PATH` to work with, instead of the current one
+    #[clap(long = "path", value_name = "PATH", global = true)]
+    given_path: Option<OsString>,
+
+    /// Platform whose `$PATH` conventions are followed, such as to modify a
+    /// Windows `Path` from Linux. Defaults to the platform `pad` is running on.
+    #[clap(long, value_enum, global = true)]
+    target: Option<Target>,
+
     #[clap(subcommand)]
     cmd: Option<SubCmd>,
 }
@@ -43,27 +78,112 @@ enum SubCmd {
     )]
     Dn(MvOpt),
     Clean(CleanOpt),
+    Ls(LsOpt),
+    Find(FindOpt),
+    Diff(DiffOpt),
+    Revert(RevertOpt),
+    Apply(ApplyOpt),
+    Batch(BatchOpt),
+    Exec(ExecOpt),
+    Shell(ShellOpt),
+    Edit(EditOpt),
+    #[cfg(feature = "tui")]
+    Tui(TuiOpt),
+    Hook(HookOpt),
+    Persist(PersistOpt),
+    Export(ExportOpt),
+    Convert(ConvertOpt),
+    Import(ImportOpt),
     #[clap(
-        about = "List the directories in `$PATH`",
+        about = "Trust a `.padrc` file so the directory hook applies it",
         author = crate_authors!(),
-        visible_alias = "echo",
     )]
-    Ls,
-    Revert(RevertOpt),
+    Allow(TrustOpt),
+    #[clap(
+        about = "Stop trusting a `.padrc` file",
+        author = crate_authors!(),
+    )]
+    Deny(TrustOpt),
+    #[clap(
+        about = "Show the location and contents of the configuration",
+        author = crate_authors!(),
+    )]
+    Config,
+}
+
+impl Opt {
+    /// The system that commands run against, with `$PATH` and the platform it
+    /// is written for replaced if they were given.
+    fn system(&self) -> GivenPath {
+        GivenPath::new(system(), self.given_path.clone(), self.target)
+    }
+
+    /// The options for printing the new `$PATH`, if the command prints one to STDOUT.
+    ///
+    /// Other commands print something else, so they always fail with an exit code.
+    fn write_opts(&self) -> Option<&WriteOpt> {
+        match &self.cmd {
+            Some(SubCmd::Add(o)) => Some(&o.write),
+            Some(SubCmd::Rm(o)) => Some(&o.write),
+            Some(SubCmd::Up(o)) | Some(SubCmd::Dn(o)) => Some(&o.write),
+            Some(SubCmd::Clean(o)) => Some(&o.write),
+            Some(SubCmd::Revert(o)) => Some(&o.write),
+            Some(SubCmd::Apply(o)) => Some(&o.write),
+            Some(SubCmd::Batch(o)) => Some(&o.write),
+            Some(SubCmd::Edit(o)) => Some(&o.write),
+            #[cfg(feature = "tui")]
+            Some(SubCmd::Tui(o)) => Some(&o.write),
+            _ => None,
+        }
+    }
+
+    /// Print the original `$PATH` after a command that prints a new one failed,
+    /// so that `export PATH=$(pad ...)` leaves it unchanged.
+    ///
+    /// It is printed in the same shell syntax that the new `$PATH` would have
+    /// been. Returns whether it was printed, which is never the case with
+    /// `--on-error fail`.
+    pub fn pass_through(&self) -> bool {
+        match (self.on_error, self.write_opts()) {
+            (OnError::Passthrough, Some(w)) => with_system(self.system(), || {
+                let original = read_raw_path().unwrap_or_default();
+                let _ = w.shell().write_set_path(&mut stdout(), &original);
+                true
+            }),
+            _ => false,
+        }
+    }
 }
 
 /// Execute the command issued from the command line.
 ///
-/// Parsing of the arguments is explicitly handled by [`parse_cli`](fn.parse_cli.html).
-pub fn execute_cli() -> io::Result<()> {
-    let opt = Opt::parse();
+/// Parsing of the arguments is handled by [`Opt::parse`](struct.Opt.html), before calling this.
+pub fn execute_cli(opt: &Opt) -> Result<(), PadError> {
+    with_system(opt.system(), || run_command(opt))
+}
+
+/// Run the subcommand, once any `$PATH` given on the command line is in place.
+fn run_command(opt: &Opt) -> Result<(), PadError> {
+    if let Err(e) = init_config() {
+        eprintln!("{}", e);
+        return Err(e.into());
+    }
 
     match &opt.cmd {
-        Some(SubCmd::Ls) | None => {
-            let vpath = read_path();
-            for p in &vpath {
-                println!("{}", p.display());
-            }
+        Some(SubCmd::Ls(ls_opts)) => {
+            ls_opts.validate()?;
+            list_path(ls_opts)?;
+        }
+        None => {
+            list_path(&LsOpt::default())?;
+        }
+        Some(SubCmd::Find(find_opts)) => {
+            find_opts.validate()?;
+            find_in_path(find_opts)?;
+        }
+        Some(SubCmd::Diff(diff_opts)) => {
+            diff_opts.validate()?;
+            diff_paths(diff_opts)?;
         }
         Some(SubCmd::Add(add_opts)) => {
             add_opts.validate()?;
@@ -89,6 +209,180 @@ pub fn execute_cli() -> io::Result<()> {
             rev_opts.validate()?;
             revert_path(rev_opts)?;
         }
+        Some(SubCmd::Apply(apply_opts)) => {
+            apply_opts.validate()?;
+            apply_manifest(apply_opts)?;
+        }
+        Some(SubCmd::Batch(batch_opts)) => {
+            batch_opts.validate()?;
+            apply_batch(batch_opts)?;
+        }
+        Some(SubCmd::Exec(exec_opts)) => {
+            exec_opts.validate()?;
+            exec_with_path(exec_opts)?;
+        }
+        Some(SubCmd::Shell(shell_opts)) => {
+            shell_opts.validate()?;
+            spawn_shell(shell_opts)?;
+        }
+        Some(SubCmd::Edit(edit_opts)) => {
+            edit_opts.validate()?;
+            edit_path(edit_opts)?;
+        }
+        #[cfg(feature = "tui")]
+        Some(SubCmd::Tui(tui_opts)) => {
+            tui_opts.validate()?;
+            edit_interactively(tui_opts)?;
+        }
+        Some(SubCmd::Hook(hook_opts)) => {
+            hook_opts.validate()?;
+            run_hook(hook_opts)?;
+        }
+        Some(SubCmd::Persist(persist_opts)) => {
+            persist_opts.validate()?;
+            persist_path(persist_opts)?;
+        }
+        Some(SubCmd::Export(export_opts)) => {
+            export_opts.validate()?;
+            export_path(export_opts)?;
+        }
+        Some(SubCmd::Convert(convert_opts)) => {
+            convert_opts.validate()?;
+            convert_path(convert_opts)?;
+        }
+        Some(SubCmd::Import(import_opts)) => {
+            import_opts.validate()?;
+            import_path(import_opts)?;
+        }
+        Some(SubCmd::Allow(trust_opts)) => {
+            trust_opts.validate()?;
+            allow_padrc(trust_opts)?;
+        }
+        Some(SubCmd::Deny(trust_opts)) => {
+            trust_opts.validate()?;
+            deny_padrc(trust_opts)?;
+        }
+        Some(SubCmd::Config) => {
+            show_config()?;
+        }
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::{memory::MemorySystem, with_system};
+
+    /// Run the command line interface against an in-memory system.
+    fn run(args: &[&str], sys: MemorySystem) -> Result<(), PadError> {
+        let opt = Opt::parse_from(["pad"].iter().chain(args));
+        with_system(sys, || execute_cli(&opt))
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn errors_have_exit_codes() {
+        let sys = MemorySystem::new()
+            .with_dir("/a")
+            .with_dir("/b")
+            .with_path("/a");
+
+        assert_eq!(
+            run(&["add", "-q", "/a"], sys.clone())
+                .unwrap_err()
+                .exit_code(),
+            4
+        );
+        assert_eq!(
+            run(&["rm", "-q", "/b"], sys.clone())
+                .unwrap_err()
+                .exit_code(),
+            3
+        );
+        assert_eq!(run(&["add", "-q", "/c"], sys).unwrap_err().exit_code(), 5);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn arguments_match_any_spelling() {
+        let sys = MemorySystem::new()
+            .with_dir("/a")
+            .with_symlink("/b", "a")
+            .with_path("/a:/gone");
+
+        // a directory in `$PATH` can be removed after it was deleted
+        run(&["rm", "-q", "/gone"], sys.clone()).unwrap();
+        assert_eq!(sys.take_stdout(), "/a\n");
+        assert_eq!(run(&["add", "-q", "/b/"], sys).unwrap_err().exit_code(), 4);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn new_path_is_printed() {
+        let sys = MemorySystem::new()
+            .with_dir("/a")
+            .with_dir("/b")
+            .with_path("/a");
+
+        run(&["add", "-q", "/b"], sys.clone()).unwrap();
+        assert_eq!(sys.take_stdout(), "/a:/b\n");
+        run(&["add", "-q", "--shell", "fish", "-p", "/b"], sys.clone()).unwrap();
+        assert_eq!(sys.take_stdout(), "set -gx PATH '/b' '/a'\n");
+
+        // a dry run only previews the changes on STDERR
+        run(&["add", "-n", "-q", "/b"], sys.clone()).unwrap();
+        assert_eq!(sys.take_stdout(), "");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn given_path_is_modified() {
+        let sys = MemorySystem::new()
+            .with_dir("/a")
+            .with_dir("/b")
+            .with_path("/a");
+
+        run(&["--path", "/b", "add", "-q", "/a"], sys.clone()).unwrap();
+        assert_eq!(sys.take_stdout(), "/b:/a\n");
+
+        // directories on another platform are only compared by name
+        let windows = |args: &[&str]| {
+            let args = [
+                &["--target", "windows", "--path", r"C:\Windows;D:\Tools\"],
+                args,
+            ]
+            .concat();
+            run(&args, sys.clone())
+        };
+        windows(&["add", "-q", r"C:\New"]).unwrap();
+        assert_eq!(sys.take_stdout(), "C:\\Windows;D:\\Tools;C:\\New\n");
+        windows(&["rm", "-q", "d:/tools"]).unwrap();
+        assert_eq!(sys.take_stdout(), "C:\\Windows\n");
+        let err = windows(&["add", "-q", "--on-error", "fail", "c:/windows/"]).unwrap_err();
+        assert_eq!(err.exit_code(), 4);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn only_path_commands_pass_through() {
+        let sys = MemorySystem::new().with_path("/a");
+        let pass_through = |args: &[&str]| {
+            let opt = Opt::parse_from(["pad"].iter().chain(args));
+            with_system(sys.clone(), || opt.pass_through())
+        };
+
+        assert!(pass_through(&["add", "/b"]));
+        assert_eq!(sys.take_stdout(), "/a\n");
+        assert!(pass_through(&["--path", "/c", "add", "/b"]));
+        assert_eq!(sys.take_stdout(), "/c\n");
+        assert!(pass_through(&["rm", "--shell", "bash", "/a"]));
+        assert_eq!(sys.take_stdout(), "export PATH='/a'\n");
+
+        assert!(!pass_through(&["--on-error", "fail", "add", "/b"]));
+        assert!(!pass_through(&[]));
+        assert!(!pass_through(&["export", "-f", "dotenv"]));
+        assert!(!pass_through(&["exec", "--", "ls"]));
+        assert_eq!(sys.take_stdout(), "");
+    }
+}
@@ -0,0 +1,241 @@
+//! User configuration for default behaviour.
+
+use crate::{
+    error::PadError,
+    shell::Shell,
+    system::{stdout, system},
+};
+use clap::{crate_name, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+/// Environment variable that overrides the location of the configuration file.
+pub const CONFIG_ENV_VAR: &str = "PAD_PATH_CONFIG";
+
+/// The configuration loaded when the application starts.
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// When to colourize output.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorChoice {
+    /// Colourize output only when writing to a terminal
+    #[default]
+    Auto,
+    /// Always colourize output
+    Always,
+    /// Never colourize output
+    Never,
+}
+
+impl ColorChoice {
+    /// Whether output should be colourized, given whether it is written to a terminal.
+    ///
+    /// Automatic colourizing respects the [`NO_COLOR`](https://no-color.org) convention.
+    pub fn enabled(&self, is_terminal: bool) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                is_terminal && system().var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+            }
+        }
+    }
+}
+
+/// Default behaviour for all subcommands.
+///
+/// Any flags given on the command line take precedence over these values.
+#[derive(Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Always add the current `$PATH` to the history before modifying it
+    pub history: bool,
+
+    /// Maximum number of `$PATH` revisions to keep in the history.
+    /// Keep everything if not set.
+    pub history_limit: Option<usize>,
+
+    /// Resolve symlinks when cleaning directory names
+    pub canonicalize: bool,
+
+    /// Replace empty and relative entries in `$PATH` with the current directory they refer to
+    pub resolve_relative: bool,
+
+    /// Shell syntax used when printing the modified `$PATH`
+    pub shell: Shell,
+
+    /// When to colourize output
+    pub color: ColorChoice,
+
+    /// Directories that can never be removed from `$PATH`
+    pub protected: Vec<PathBuf>,
+
+    /// Match directories given to `pad rm`, `pad up`, and `pad dn` by part of their name
+    pub fuzzy: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            history: false,
+            history_limit: None,
+            canonicalize: true,
+            resolve_relative: false,
+            shell: Shell::default(),
+            color: ColorChoice::default(),
+            protected: vec![],
+            fuzzy: false,
+        }
+    }
+}
+
+impl Config {
+    /// Parse the configuration from the contents of a TOML file.
+    pub fn parse(s: &str) -> io::Result<Config> {
+        toml::from_str(s).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Load the configuration from a file.
+    ///
+    /// A missing file is not an error, and results in the default configuration.
+    pub fn from_file(filepath: &Path) -> io::Result<Config> {
+        let sys = system();
+        if !sys.exists(filepath) {
+            return Ok(Config::default());
+        }
+
+        match sys.read_to_string(filepath).and_then(|s| Config::parse(&s)) {
+            Ok(cfg) => Ok(cfg),
+            Err(e) => Err(PadError::reading_file(
+                format!(
+                    "Could not read configuration file `{}`. {}",
+                    filepath.display(),
+                    e
+                ),
+                e,
+            )),
+        }
+    }
+}
+
+/// Find the directory holding the configuration and history files.
+pub fn get_config_dir() -> PathBuf {
+    // check if $XDG_CONFIG_HOME is set
+    let sys = system();
+    let mut cfg_path = match sys.config_dir() {
+        Some(dir) => dir,
+        // if not set, make it the default $HOME/.config
+        None => {
+            if let Some(mut dir) = sys.home_dir() {
+                dir.push(".config");
+                dir
+            } else {
+                PathBuf::new()
+            }
+        }
+    };
+
+    // get config from within $XDG_CONFIG_HOME
+    cfg_path.push(crate_name!());
+    cfg_path
+}
+
+/// Check multiple locations for a configuration file and return the highest
+/// priority one.
+pub fn get_config_filepath() -> PathBuf {
+    match system().var_os(CONFIG_ENV_VAR) {
+        Some(p) if !p.is_empty() => PathBuf::from(p),
+        _ => get_config_dir().join("config.toml"),
+    }
+}
+
+/// Load the user's configuration so that it is available through [`config`].
+pub fn init_config() -> io::Result<()> {
+    let cfg = Config::from_file(&get_config_filepath())?;
+    // if the configuration has already been set, keep the existing one
+    let _ = CONFIG.set(cfg);
+    Ok(())
+}
+
+/// Get the user's configuration.
+///
+/// If [`init_config`] has not been called, this is the default configuration.
+pub fn config() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+/// Print the location of the configuration file and the settings in effect.
+pub fn show_config() -> io::Result<()> {
+    let settings =
+        toml::to_string(config()).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    writeln!(stdout(), "# {}", get_config_filepath().display())?;
+    write!(stdout(), "{}", settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_file_is_default() {
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn parse_all_fields() {
+        let observed = Config::parse(
+            r#"
+            history = true
+            history_limit = 50
+            canonicalize = false
+            resolve_relative = true
+            shell = "fish"
+            color = "never"
+            protected = ["/usr/bin"]
+            fuzzy = true
+            "#,
+        )
+        .unwrap();
+
+        let expected = Config {
+            history: true,
+            history_limit: Some(50),
+            canonicalize: false,
+            resolve_relative: true,
+            shell: Shell::Fish,
+            color: ColorChoice::Never,
+            protected: vec![PathBuf::from("/usr/bin")],
+            fuzzy: true,
+        };
+        assert_eq!(observed, expected);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn missing_file_is_default() {
+        use crate::system::{memory::MemorySystem, with_system};
+
+        let sys = MemorySystem::new().with_contents("/cfg/config.toml", "history = true");
+        with_system(sys, || {
+            assert!(
+                Config::from_file(Path::new("/cfg/config.toml"))
+                    .unwrap()
+                    .history
+            );
+            assert_eq!(
+                Config::from_file(Path::new("/cfg/missing.toml")).unwrap(),
+                Config::default()
+            );
+        });
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        assert!(Config::parse("colour = \"never\"").is_err());
+    }
+}
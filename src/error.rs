@@ -0,0 +1,218 @@
+//! Errors that can occur while modifying `$PATH`, and the exit codes they map to.
+//!
+//! Functions throughout the crate return `io::Result`, and a `PadError` travels
+//! inside the `io::Error` so that `main` can recover it and exit with a code
+//! that scripts can rely on.
+
+use clap::ValueEnum;
+use std::{error::Error, fmt, io, path::PathBuf};
+
+/// What to do when a command fails.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OnError {
+    /// For commands that print a new `$PATH`, print the original `$PATH` and exit successfully, so `export PATH=$(pad ...)` leaves it unchanged
+    #[default]
+    Passthrough,
+    /// Print nothing and exit with a non-zero code describing the error
+    Fail,
+}
+
+/// Everything that can go wrong when running a command.
+#[derive(Debug)]
+pub enum PadError {
+    /// A directory that should be in `$PATH` isn't
+    NotFound(PathBuf),
+    /// A directory given by part of its name matches more than one directory in `$PATH`
+    Ambiguous(PathBuf, Vec<PathBuf>),
+    /// A directory being added is already in `$PATH`
+    AlreadyExists(PathBuf),
+    /// A directory doesn't exist on the file system
+    NonexistentDir(PathBuf),
+    /// There is no history to revert to
+    HistoryEmpty,
+    /// The history has fewer revisions than requested
+    HistoryTooShort(u128),
+    /// A `$PATH` can't be built or represented
+    InvalidPath(String),
+    /// A protected directory would be removed from `$PATH`
+    Protected(PathBuf),
+    /// The user cancelled the command
+    Cancelled,
+    /// A command could not be started
+    CannotRun(String, io::Error),
+    /// Invalid arguments or input
+    InvalidInput(String),
+    /// A file given to a command doesn't exist
+    MissingFile(String),
+    /// A file, like a manifest or the configuration, can't be understood
+    InvalidFile(String),
+    /// Something isn't supported for a shell or platform
+    Unsupported(String),
+    /// No process has the ID given with `--pid`
+    ProcessNotFound(u32),
+    /// Any other error, such as failing to read or write a file
+    Io(io::Error),
+}
+
+impl PadError {
+    /// The code the process exits with for this error, when using `--on-error fail`.
+    ///
+    /// These are stable, so that scripts can depend on them.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            PadError::Io(e) if e.kind() == io::ErrorKind::InvalidInput => 2,
+            PadError::Io(_) => 1,
+            PadError::InvalidInput(_) => 2,
+            PadError::NotFound(_) => 3,
+            PadError::AlreadyExists(_) => 4,
+            PadError::NonexistentDir(_) => 5,
+            PadError::HistoryEmpty | PadError::HistoryTooShort(_) => 6,
+            PadError::InvalidPath(_) => 7,
+            PadError::Protected(_) => 8,
+            PadError::Cancelled => 9,
+            PadError::Ambiguous(..) => 10,
+            PadError::MissingFile(_) => 11,
+            PadError::InvalidFile(_) => 12,
+            PadError::Unsupported(_) => 13,
+            PadError::ProcessNotFound(_) => 14,
+            // the same codes as a shell uses
+            PadError::CannotRun(_, e) if e.kind() == io::ErrorKind::NotFound => 127,
+            PadError::CannotRun(..) => 126,
+        }
+    }
+
+    /// Describe a failure to read a file with `msg`.
+    ///
+    /// Contents that couldn't be understood make it an [`PadError::InvalidFile`],
+    /// and anything else stays an `io::Error` of the same kind.
+    pub fn reading_file(msg: String, e: io::Error) -> io::Error {
+        match e.kind() {
+            io::ErrorKind::InvalidData => PadError::InvalidFile(msg).into(),
+            kind => io::Error::new(kind, msg),
+        }
+    }
+
+    /// The closest kind of `io::Error`.
+    fn kind(&self) -> io::ErrorKind {
+        match self {
+            PadError::NotFound(_)
+            | PadError::HistoryEmpty
+            | PadError::MissingFile(_)
+            | PadError::ProcessNotFound(_) => io::ErrorKind::NotFound,
+            PadError::AlreadyExists(_) => io::ErrorKind::AlreadyExists,
+            PadError::NonexistentDir(_) | PadError::InvalidPath(_) | PadError::InvalidFile(_) => {
+                io::ErrorKind::InvalidData
+            }
+            PadError::HistoryTooShort(_) | PadError::InvalidInput(_) | PadError::Ambiguous(..) => {
+                io::ErrorKind::InvalidInput
+            }
+            PadError::Protected(_) => io::ErrorKind::PermissionDenied,
+            PadError::Cancelled => io::ErrorKind::Interrupted,
+            PadError::Unsupported(_) => io::ErrorKind::Unsupported,
+            PadError::CannotRun(_, e) => e.kind(),
+            PadError::Io(e) => e.kind(),
+        }
+    }
+}
+
+impl fmt::Display for PadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PadError::NotFound(d) => write!(
+                f,
+                "Directory `{}` not found in `$PATH`. No changes made.",
+                d.display()
+            ),
+            PadError::Ambiguous(d, candidates) => {
+                write!(
+                    f,
+                    "Directory `{}` matches more than one directory in `$PATH`. Give more of its name to choose one of:",
+                    d.display()
+                )?;
+                for c in candidates {
+                    write!(f, "\n  {}", c.display())?;
+                }
+                Ok(())
+            }
+            PadError::AlreadyExists(d) => write!(
+                f,
+                "Directory `{}` already exists in `$PATH`. Use `pad up/dn` to change priority of this directory, or `pad add -f` to force it. No changes made.",
+                d.display()
+            ),
+            PadError::NonexistentDir(d) => write!(
+                f,
+                "Directory `{}` does not exist. Please double check the directories you intend to use.",
+                d.display()
+            ),
+            PadError::HistoryEmpty => write!(f, "History file not found. Nothing to revert to."),
+            PadError::HistoryTooShort(n) => write!(
+                f,
+                "History does not contain {} revision(s). Please specify a smaller revision number.",
+                n
+            ),
+            PadError::InvalidPath(reason) => write!(f, "Invalid `$PATH`. {}", reason),
+            PadError::Protected(d) => write!(
+                f,
+                "Directory `{}` is protected and cannot be removed from `$PATH`. No changes made.",
+                d.display()
+            ),
+            PadError::Cancelled => write!(f, "Cancelled. No changes made."),
+            PadError::CannotRun(cmd, e) => write!(f, "Could not run `{}`. {}", cmd, e),
+            PadError::InvalidInput(msg)
+            | PadError::MissingFile(msg)
+            | PadError::InvalidFile(msg)
+            | PadError::Unsupported(msg) => write!(f, "{}", msg),
+            PadError::ProcessNotFound(pid) => write!(f, "No process with ID {} found.", pid),
+            PadError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for PadError {}
+
+impl From<PadError> for io::Error {
+    fn from(e: PadError) -> io::Error {
+        match e {
+            PadError::Io(e) => e,
+            e => io::Error::new(e.kind(), e),
+        }
+    }
+}
+
+impl From<io::Error> for PadError {
+    fn from(e: io::Error) -> PadError {
+        // recover the original error if it was converted into an `io::Error`
+        match e.get_ref().is_some_and(|inner| inner.is::<PadError>()) {
+            true => *e.into_inner().unwrap().downcast::<PadError>().unwrap(),
+            false => PadError::Io(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recovered_from_io_error() {
+        let e: io::Error = PadError::NotFound(PathBuf::from("/a")).into();
+        assert_eq!(e.kind(), io::ErrorKind::NotFound);
+        assert_eq!(PadError::from(e).exit_code(), 3);
+
+        let e = io::Error::other("other");
+        assert_eq!(PadError::from(e).exit_code(), 1);
+    }
+
+    #[test]
+    fn unreadable_files() {
+        let e = io::Error::new(io::ErrorKind::InvalidData, "bad");
+        let e = PadError::reading_file(String::from("Could not read `a`. bad"), e);
+        assert_eq!(e.to_string(), "Could not read `a`. bad");
+        assert_eq!(PadError::from(e).exit_code(), 12);
+
+        let e = io::Error::new(io::ErrorKind::PermissionDenied, "denied");
+        let e = PadError::reading_file(String::from("Could not read `a`. denied"), e);
+        assert_eq!(e.kind(), io::ErrorKind::PermissionDenied);
+        assert_eq!(PadError::from(e).exit_code(), 1);
+    }
+}
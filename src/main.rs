@@ -1,19 +1,30 @@
 //! Intuitively modify your `$PATH`.
 
 mod cli;
+mod config;
+mod error;
+mod manifest;
+mod padrc;
 mod path;
+mod shell;
+mod system;
+#[cfg(feature = "tui")]
+mod tui;
 
-use crate::cli::execute_cli;
-use crate::path::read::read_raw_path;
+use crate::cli::{execute_cli, Opt};
+use clap::Parser;
+use std::process::ExitCode;
 
 /// Run the command line interface and print the adjusted `$PATH`.
-fn main() {
-    match execute_cli() {
+fn main() -> ExitCode {
+    let opt = Opt::parse();
+    match execute_cli(&opt) {
         // if no error, do nothing
-        Ok(_) => {}
-        Err(_) => {
-            // if there is an error, print the error to STDERR and print the original path to STDOUT
-            println!("{}", read_raw_path().unwrap().to_string_lossy());
-        }
-    };
+        Ok(_) => ExitCode::SUCCESS,
+        // the error has already been printed to STDERR
+        Err(e) => match opt.pass_through() {
+            true => ExitCode::SUCCESS,
+            false => ExitCode::from(e.exit_code()),
+        },
+    }
 }
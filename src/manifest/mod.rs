@@ -0,0 +1,330 @@
+//! Declarative descriptions of `$PATH`.
+//!
+//! A manifest is a TOML file listing the directories that should be in `$PATH`,
+//! organized into groups with priority tiers.
+//!
+//! ```toml
+//! [vars]
+//! tools = "/opt/tools"
+//!
+//! [[group]]
+//! name = "user"
+//! priority = 0
+//! entries = [
+//!     { path = "~/.local/bin" },
+//!     { path = "${tools}/bin", exists = true },
+//!     { path = "/opt/homebrew/bin", os = "macos", missing = "brew" },
+//! ]
+//! ```
+
+use crate::{
+    error::PadError,
+    path::{add::add_dirs, clean::clean_dir_name, executables::find_command},
+    system::system,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    env::consts::OS,
+    io,
+    path::{Path, PathBuf},
+};
+
+/// An ordered description of the directories in `$PATH`.
+#[derive(Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Manifest {
+    /// Variables that can be referenced in entries as `${name}`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub vars: BTreeMap<String, String>,
+
+    /// Groups of entries
+    #[serde(rename = "group")]
+    pub groups: Vec<Group>,
+}
+
+/// Where a group of entries is placed relative to the existing `$PATH`.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Position {
+    /// Before the existing `$PATH`, making the entries higher priority
+    #[default]
+    Prepend,
+    /// After the existing `$PATH`, making the entries lower priority
+    Append,
+}
+
+/// A set of entries that share a priority tier.
+#[derive(Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default)]
+pub struct Group {
+    /// Name of the group, for reference only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Groups with lower values are placed earlier in `$PATH`
+    pub priority: i64,
+
+    /// Placement relative to the existing `$PATH`
+    pub position: Position,
+
+    /// Conditions that must all hold for any of the entries to be included
+    #[serde(flatten)]
+    pub when: Condition,
+
+    /// Directories in this group, in order of priority
+    pub entries: Vec<Entry>,
+}
+
+/// A single directory in `$PATH`.
+#[derive(Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+pub struct Entry {
+    /// The directory, which may reference variables
+    pub path: String,
+
+    /// Conditions that must all hold for this entry to be included
+    #[serde(flatten)]
+    pub when: Condition,
+}
+
+/// Conditions for including entries in `$PATH`.
+#[derive(Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default)]
+pub struct Condition {
+    /// Only include the directory if it exists
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub exists: bool,
+
+    /// Only include on this operating system (e.g. `linux`, `macos`, `windows`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub os: Option<String>,
+
+    /// Only include on the machine with this hostname
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+
+    /// Only include if this command cannot be found in the existing `$PATH`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub missing: Option<String>,
+}
+
+impl Condition {
+    /// Check whether all conditions hold, given the `$PATH` that entries are added to.
+    fn holds(&self, dir: Option<&Path>, base: &[PathBuf]) -> bool {
+        if self.exists && !dir.is_none_or(|d| system().exists(d)) {
+            return false;
+        }
+        if let Some(os) = &self.os {
+            if !os.eq_ignore_ascii_case(OS) {
+                return false;
+            }
+        }
+        if let Some(host) = &self.hostname {
+            let current = hostname::get().unwrap_or_default();
+            if !host.eq_ignore_ascii_case(&current.to_string_lossy()) {
+                return false;
+            }
+        }
+        if let Some(cmd) = &self.missing {
+            if find_command(cmd, base).is_some() {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl Manifest {
+    /// Parse a manifest from the contents of a TOML file.
+    pub fn parse(s: &str) -> io::Result<Manifest> {
+        toml::from_str(s).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Load a manifest from a file.
+    pub fn from_file(filepath: &Path) -> io::Result<Manifest> {
+        match system()
+            .read_to_string(filepath)
+            .and_then(|s| Manifest::parse(&s))
+        {
+            Ok(m) => Ok(m),
+            Err(e) => Err(PadError::reading_file(
+                format!("Could not read manifest `{}`. {}", filepath.display(), e),
+                e,
+            )),
+        }
+    }
+
+    /// Compute the directories in `$PATH` after applying this manifest to `current`.
+    ///
+    /// With `replace`, the directories in `current` are left out, but conditions
+    /// like `missing` are still checked against them.
+    pub fn resolve(&self, current: Vec<PathBuf>, replace: bool) -> io::Result<Vec<PathBuf>> {
+        let (prepended, appended) = self.entries(&current, &system().current_dir()?)?;
+        let base = match replace {
+            true => vec![],
+            false => current,
+        };
+        let newpath = add_dirs(base, prepended, true);
+        Ok(add_dirs(newpath, appended, false))
+    }
+
+    /// Find the directories to prepend and append to `base`, respectively.
+    ///
+    /// Relative directories are resolved with respect to `root`.
+    pub fn entries(
+        &self,
+        base: &[PathBuf],
+        root: &Path,
+    ) -> io::Result<(Vec<PathBuf>, Vec<PathBuf>)> {
+        let mut groups: Vec<&Group> = self.groups.iter().collect();
+        // stable sort, so groups in the same tier stay in the order they were written
+        groups.sort_by_key(|g| g.priority);
+
+        let mut prepended: Vec<PathBuf> = vec![];
+        let mut appended: Vec<PathBuf> = vec![];
+        for g in groups {
+            if !g.when.holds(None, base) {
+                continue;
+            }
+            for e in &g.entries {
+                let dir = clean_dir_name(&root.join(self.expand(&e.path)?));
+                if !e.when.holds(Some(&dir), base) {
+                    continue;
+                }
+                match g.position {
+                    Position::Prepend => prepended.push(dir),
+                    Position::Append => appended.push(dir),
+                }
+            }
+        }
+
+        Ok((prepended, appended))
+    }
+
+    /// Expand `~` and `${name}` variable references in an entry.
+    ///
+    /// Variables are looked up in the manifest first, then in the environment.
+    pub fn expand(&self, s: &str) -> io::Result<String> {
+        let mut expanded = String::new();
+        let mut rest = s;
+
+        if let Some(after_tilde) = rest.strip_prefix('~') {
+            if after_tilde.is_empty() || after_tilde.starts_with(['/', '\\']) {
+                if let Some(home) = system().home_dir() {
+                    expanded.push_str(&home.to_string_lossy());
+                    rest = after_tilde;
+                }
+            }
+        }
+
+        while let Some(start) = rest.find("${") {
+            expanded.push_str(&rest[..start]);
+            let end = match rest[start..].find('}') {
+                Some(i) => start + i,
+                None => {
+                    return Err(PadError::InvalidFile(format!(
+                        "Unterminated variable reference in `{}`.",
+                        s
+                    ))
+                    .into())
+                }
+            };
+            let name = &rest[(start + 2)..end];
+            let value = match self.vars.get(name) {
+                Some(v) => v.clone(),
+                None => system()
+                    .var_os(name)
+                    .and_then(|v| v.into_string().ok())
+                    .ok_or_else(|| {
+                        PadError::InvalidFile(format!(
+                            "Variable `{}` referenced in `{}` is not defined.",
+                            name, s
+                        ))
+                    })?,
+            };
+            expanded.push_str(&value);
+            rest = &rest[(end + 1)..];
+        }
+        expanded.push_str(rest);
+
+        Ok(expanded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::dirs;
+    #[cfg(unix)]
+    use crate::system::{memory::MemorySystem, with_system};
+
+    fn manifest(s: &str) -> Manifest {
+        Manifest::parse(s).unwrap()
+    }
+
+    #[test]
+    fn expand_manifest_vars() {
+        let m = manifest("[vars]\ntools = \"/opt/tools\"");
+        assert_eq!(m.expand("${tools}/bin").unwrap(), "/opt/tools/bin");
+    }
+
+    #[test]
+    fn expand_undefined_var() {
+        let m = Manifest::default();
+        assert!(m.expand("${PAD_PATH_UNDEFINED_VAR}/bin").is_err());
+        assert!(m.expand("${unterminated/bin").is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn groups_sorted_by_priority() {
+        let m = manifest(
+            r#"
+            [[group]]
+            priority = 10
+            entries = [{ path = "/low" }]
+
+            [[group]]
+            priority = 0
+            entries = [{ path = "/high" }]
+
+            [[group]]
+            position = "append"
+            entries = [{ path = "/last" }]
+            "#,
+        );
+        let observed = m.resolve(vec![PathBuf::from("/base")], false).unwrap();
+        assert_eq!(observed, dirs(&["/high", "/low", "/base", "/last"]));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn conditions_exclude_entries() {
+        let m = manifest(
+            r#"
+            [[group]]
+            entries = [
+                { path = "/pad-path/does/not/exist", exists = true },
+                { path = "/other-os", os = "not-an-os" },
+                { path = "/kept" },
+            ]
+            "#,
+        );
+        let observed = m.resolve(vec![], false).unwrap();
+        assert_eq!(observed, vec![PathBuf::from("/kept")]);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn replacing_checks_current_path() {
+        let m = manifest(
+            r#"
+            [[group]]
+            entries = [{ path = "/opt/homebrew/bin", missing = "brew" }, { path = "/kept" }]
+            "#,
+        );
+        let sys = MemorySystem::new().with_file("/usr/bin/brew", true);
+        let observed = with_system(sys, || m.resolve(vec![PathBuf::from("/usr/bin")], true));
+        assert_eq!(observed.unwrap(), vec![PathBuf::from("/kept")]);
+    }
+}
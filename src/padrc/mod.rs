@@ -0,0 +1,170 @@
+//! Project-local `$PATH` rules stored in `.padrc` files.
+//!
+//! A `.padrc` file is a [manifest](crate::manifest) whose relative entries are
+//! resolved with respect to the directory containing it.
+//! Since entering a directory should not be able to silently change `$PATH`,
+//! a `.padrc` file is only used once it has been explicitly trusted.
+//! Trust is tied to the contents of the file, so any modification requires it
+//! to be trusted again.
+
+use crate::{config::get_config_dir, error::PadError, system::system};
+use clap::Parser;
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Name of the file holding project-local `$PATH` rules.
+pub const PADRC_FILENAME: &str = ".padrc";
+
+#[derive(Debug, Parser)]
+pub struct TrustOpt {
+    /// Directory containing the `.padrc` file, or the file itself
+    #[clap(default_value = ".")]
+    dir: PathBuf,
+
+    /// Don't print warnings.
+    #[clap(short, long)]
+    quiet: bool,
+}
+
+impl TrustOpt {
+    /// Validate options
+    pub fn validate(&self) -> io::Result<()> {
+        if !system().is_file(&self.padrc()) {
+            let err_nonexistent: io::Error = PadError::MissingFile(format!(
+                "No `{}` file found in `{}`.",
+                PADRC_FILENAME,
+                self.dir.display()
+            ))
+            .into();
+
+            if !self.quiet {
+                eprintln!("{}", err_nonexistent);
+            }
+
+            return Err(err_nonexistent);
+        }
+
+        Ok(())
+    }
+
+    /// The `.padrc` file to trust or distrust.
+    fn padrc(&self) -> PathBuf {
+        match system().is_dir(&self.dir) {
+            true => self.dir.join(PADRC_FILENAME),
+            false => self.dir.clone(),
+        }
+    }
+}
+
+/// Find the closest `.padrc` file in a directory or any of its ancestors.
+pub fn find_padrc(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|d| d.join(PADRC_FILENAME))
+        .find(|p| system().is_file(p))
+}
+
+/// Location of the list of trusted `.padrc` files.
+pub fn get_trust_filepath() -> PathBuf {
+    get_config_dir().join("trusted")
+}
+
+/// Hash the contents of a file with 64-bit FNV-1a.
+///
+/// This is stable across platforms and compiler versions, unlike the standard
+/// library's hasher.
+fn hash_contents(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |h, b| {
+        (h ^ u64::from(*b)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Format the line in the trust file for a `.padrc` file.
+fn trust_entry(padrc: &Path) -> io::Result<(PathBuf, String)> {
+    let sys = system();
+    let padrc = sys.canonicalize(padrc)?;
+    let hash = hash_contents(&sys.read(&padrc)?);
+    let entry = format!("{:016x} {}", hash, padrc.display());
+    Ok((padrc, entry))
+}
+
+/// Read the entries in the trust file, excluding those for a given `.padrc` file.
+fn other_trust_entries(padrc: &Path) -> io::Result<Vec<String>> {
+    let sys = system();
+    let trust_filepath = get_trust_filepath();
+    if !sys.exists(&trust_filepath) {
+        return Ok(vec![]);
+    }
+
+    let suffix = format!(" {}", padrc.display());
+    Ok(sys
+        .read_to_string(&trust_filepath)?
+        .lines()
+        .filter(|l| !l.is_empty() && !l.ends_with(&suffix))
+        .map(String::from)
+        .collect())
+}
+
+/// Replace the contents of the trust file.
+fn write_trust_entries(entries: &[String]) -> io::Result<()> {
+    let sys = system();
+    sys.create_dir_all(&get_config_dir())?;
+    let mut contents = entries.join("\n");
+    contents.push('\n');
+    sys.write(&get_trust_filepath(), contents.as_bytes())
+}
+
+/// Check if a `.padrc` file, with its current contents, has been trusted.
+pub fn is_trusted(padrc: &Path) -> io::Result<bool> {
+    let sys = system();
+    let trust_filepath = get_trust_filepath();
+    if !sys.exists(&trust_filepath) {
+        return Ok(false);
+    }
+
+    let (_, entry) = trust_entry(padrc)?;
+    Ok(sys
+        .read_to_string(&trust_filepath)?
+        .lines()
+        .any(|l| l == entry))
+}
+
+/// Trust a `.padrc` file so that it is applied when entering its directory.
+pub fn allow_padrc(opts: &TrustOpt) -> io::Result<()> {
+    let (padrc, entry) = trust_entry(&opts.padrc())?;
+    let mut entries = other_trust_entries(&padrc)?;
+    entries.push(entry);
+    write_trust_entries(&entries)?;
+
+    if !opts.quiet {
+        eprintln!(
+            "Trusted `{}`. It will be applied the next time you change directory.",
+            padrc.display()
+        );
+    }
+    Ok(())
+}
+
+/// Stop trusting a `.padrc` file.
+pub fn deny_padrc(opts: &TrustOpt) -> io::Result<()> {
+    let padrc = system().canonicalize(&opts.padrc())?;
+    write_trust_entries(&other_trust_entries(&padrc)?)?;
+
+    if !opts.quiet {
+        eprintln!("No longer trusting `{}`.", padrc.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fnv_hash_is_stable() {
+        assert_eq!(hash_contents(b""), 0xcbf29ce484222325);
+        assert_eq!(hash_contents(b"a"), 0xaf63dc4c8601ec8c);
+    }
+}
@@ -1,13 +1,14 @@
 //! Add a directory or multiple directories to the `$PATH`.
 
+use crate::{error::PadError, system::system};
 use clap::{crate_authors, Parser};
-use std::collections::HashSet;
 use std::io;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 
 use super::clean::clean_dirs_names;
-use super::read::{combine_path_like, read_path};
-use super::write::replace_path;
+use super::matching::find_dir;
+use super::read::{combine_path_like, foreign_target, read_path};
+use super::write::{replace_path, WriteOpt};
 
 #[derive(Debug, Parser)]
 #[clap(
@@ -27,17 +28,8 @@ pub struct AddOpt {
     #[clap(short, long)]
     prepend: bool,
 
-    /// Don't print warnings when modifying `$PATH`.
-    #[clap(short, long)]
-    quiet: bool,
-
-    /// Add current `$PATH` to the history
-    #[clap(short = 'H', long)]
-    history: bool,
-
-    /// Don't do anything, just preview what this command would do
-    #[clap(short = 'n', long = "dry-run")]
-    dry_run: bool,
+    #[clap(flatten)]
+    pub write: WriteOpt,
 }
 
 impl AddOpt {
@@ -48,45 +40,20 @@ impl AddOpt {
         }
 
         // check if directory(ies) exist
-        for d in &self.dirs {
-            if !d.exists() {
-                let err_nonexistent = io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    format!(
-                        "Directory `{}`  does not exist. Please double check the directories you intend to add.",
-                        d.display()
-                    )
-                );
-
-                if !self.quiet {
-                    eprintln!("{}", err_nonexistent);
-                }
-
-                return Err(err_nonexistent);
+        if let Err(e) = check_dirs_exist(&self.dirs) {
+            if !self.write.quiet {
+                eprintln!("{}", e);
             }
+
+            return Err(e);
         }
 
-        // check that the directories to be added don't already exist in the PATH
-        let cleaned_dirs: Vec<PathBuf> = clean_dirs_names(&self.dirs);
-        let _current_dirs: HashSet<PathBuf> = read_path().iter().cloned().collect();
-        let _new_dirs: HashSet<PathBuf> = cleaned_dirs.iter().cloned().collect();
-
-        let _intersecting_dirs: Vec<&Path> = _current_dirs
-            .intersection(&_new_dirs)
-            .into_iter()
-            .map(|d| d.as_path())
-            .collect();
-
-        if !_intersecting_dirs.is_empty() {
-            let err_duplicated = io::Error::new(
-                io::ErrorKind::AlreadyExists,
-                format!(
-                    "Directory `{}` already exists in `$PATH`. Use `pad up/dn` to change priority of this directory, or `pad add -f` to force it. No changes made.",
-                    _intersecting_dirs[0].display()
-                )
-            );
-
-            if !self.quiet {
+        // check that the directories to be added aren't already in the PATH
+        let current_path = read_path();
+        if let Some(i) = self.dirs.iter().find_map(|d| find_dir(&current_path, d)) {
+            let err_duplicated: io::Error = PadError::AlreadyExists(current_path[i].clone()).into();
+
+            if !self.write.quiet {
                 eprintln!("{}", err_duplicated);
             }
 
@@ -100,26 +67,46 @@ impl AddOpt {
 /// Add the given directory to the `$PATH` environment variable
 pub fn add_to_path(opts: &AddOpt) -> io::Result<()> {
     // read the path, clean each entry, and convert into Vec<PathBuf>
-    let mut current_path: Vec<PathBuf> = read_path();
-    let mut cleaned_dirs: Vec<PathBuf> = clean_dirs_names(&opts.dirs);
+    let current_path: Vec<PathBuf> = read_path();
+    let cleaned_dirs: Vec<PathBuf> = clean_dirs_names(&opts.dirs);
 
-    let newpath = match opts.prepend {
-        true => {
-            cleaned_dirs.append(&mut current_path);
-            combine_path_like(cleaned_dirs)
-        }
-        false => {
-            current_path.append(&mut cleaned_dirs);
-            combine_path_like(current_path)
-        }
-    }?;
-    match replace_path(newpath, opts.dry_run, opts.history, opts.quiet) {
+    let newpath = combine_path_like(add_dirs(current_path, cleaned_dirs, opts.prepend))?;
+    match replace_path(newpath, &opts.write) {
         Ok(()) => Ok(()),
         Err(e) => {
-            if !opts.quiet {
+            if !opts.write.quiet {
                 eprintln!("{}", e);
             }
             Err(e)
         }
     }
 }
+
+/// Check that every directory to be added exists.
+///
+/// Directories on another platform can't be checked, so they are assumed to exist.
+pub fn check_dirs_exist(dirs: &[PathBuf]) -> io::Result<()> {
+    if foreign_target().is_some() {
+        return Ok(());
+    }
+
+    let sys = system();
+    match dirs.iter().find(|d| !sys.exists(d)) {
+        Some(d) => Err(PadError::NonexistentDir(d.clone()).into()),
+        None => Ok(()),
+    }
+}
+
+/// Add directories to the beginning or end of a list of directories.
+pub fn add_dirs(mut current: Vec<PathBuf>, mut dirs: Vec<PathBuf>, prepend: bool) -> Vec<PathBuf> {
+    match prepend {
+        true => {
+            dirs.append(&mut current);
+            dirs
+        }
+        false => {
+            current.append(&mut dirs);
+            current
+        }
+    }
+}
@@ -0,0 +1,75 @@
+//! Set `$PATH` from a declarative manifest.
+
+use super::{
+    read::{combine_path_like, read_path},
+    write::{replace_path, WriteOpt},
+};
+use crate::{error::PadError, manifest::Manifest, system::system};
+use clap::{crate_authors, Parser};
+use std::{io, path::PathBuf};
+
+#[derive(Debug, Parser)]
+#[clap(
+    about = "Apply a manifest describing `$PATH`",
+    author = crate_authors!(),
+)]
+pub struct ApplyOpt {
+    /// Manifest file describing the directories in `$PATH`
+    manifest: PathBuf,
+
+    /// Build `$PATH` from the manifest alone, instead of adding to the current `$PATH`
+    #[clap(short, long)]
+    replace: bool,
+
+    #[clap(flatten)]
+    pub write: WriteOpt,
+}
+
+impl ApplyOpt {
+    /// Validate options
+    pub fn validate(&self) -> io::Result<()> {
+        if !system().is_file(&self.manifest) {
+            let err_nonexistent: io::Error = PadError::MissingFile(format!(
+                "Manifest `{}` does not exist.",
+                self.manifest.display()
+            ))
+            .into();
+
+            if !self.write.quiet {
+                eprintln!("{}", err_nonexistent);
+            }
+
+            return Err(err_nonexistent);
+        }
+
+        Ok(())
+    }
+}
+
+/// Compute `$PATH` from a manifest and replace the current one.
+pub fn apply_manifest(opts: &ApplyOpt) -> io::Result<()> {
+    let newpath = Manifest::from_file(&opts.manifest)
+        .and_then(|m| m.resolve(read_path(), opts.replace))
+        .and_then(combine_path_like);
+    let newpath = match newpath {
+        Ok(p) => p,
+        Err(e) => {
+            if !opts.write.quiet {
+                eprintln!("{}", e);
+            }
+
+            return Err(e);
+        }
+    };
+
+    match replace_path(newpath, &opts.write) {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            if !opts.write.quiet {
+                eprintln!("Could not apply manifest. {}", e);
+            }
+
+            Err(e)
+        }
+    }
+}
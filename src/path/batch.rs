@@ -0,0 +1,272 @@
+//! Apply a script of operations to `$PATH` all at once.
+
+use super::{
+    clean::clean_dirs,
+    matching::find_dir,
+    ops::{not_found, OpsOpt},
+    priority::move_dir,
+    read::{combine_path_like, read_path},
+    write::{replace_path, WriteOpt},
+};
+use crate::{error::PadError, system::system};
+use clap::{crate_authors, Parser};
+use std::{
+    io::{self, Read},
+    path::PathBuf,
+};
+
+#[derive(Debug, Parser)]
+#[clap(
+    about = "Apply a script of operations to `$PATH` at once",
+    author = crate_authors!(),
+)]
+pub struct BatchOpt {
+    /// Script of operations, one per line. Reads from STDIN if not given, or if `-`.
+    script: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub write: WriteOpt,
+}
+
+impl BatchOpt {
+    /// Validate options
+    pub fn validate(&self) -> io::Result<()> {
+        match &self.script {
+            Some(s) if s.as_os_str() != "-" && !system().is_file(s) => {
+                let err_nonexistent: io::Error =
+                    PadError::MissingFile(format!("Script `{}` does not exist.", s.display()))
+                        .into();
+
+                if !self.write.quiet {
+                    eprintln!("{}", err_nonexistent);
+                }
+
+                Err(err_nonexistent)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Read the contents of the script.
+    fn read_script(&self) -> io::Result<String> {
+        match &self.script {
+            Some(s) if s.as_os_str() != "-" => system().read_to_string(s),
+            _ => {
+                let mut contents = String::new();
+                io::stdin().read_to_string(&mut contents)?;
+                Ok(contents)
+            }
+        }
+    }
+}
+
+/// Split a line into words, keeping text within single or double quotes together.
+pub fn split_words(line: &str) -> io::Result<Vec<String>> {
+    let mut words = vec![];
+    let mut word: Option<String> = None;
+    let mut quote: Option<char> = None;
+
+    for c in line.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => word.get_or_insert_with(String::new).push(c),
+            None if c == '\'' || c == '"' => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            None if c.is_whitespace() => words.extend(word.take()),
+            None => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+
+    if quote.is_some() {
+        return Err(PadError::InvalidInput(String::from("Unterminated quote.")).into());
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// Error for an operation that was given the wrong arguments.
+fn usage(usage: &str) -> io::Error {
+    PadError::InvalidInput(format!("Invalid arguments. Usage: `{}`.", usage)).into()
+}
+
+/// Parse the number of spots to move a directory, which defaults to 1.
+fn parse_number(arg: Option<&String>, usage_text: &str) -> io::Result<usize> {
+    match arg {
+        Some(n) => n.parse().map_err(|_| usage(usage_text)),
+        None => Ok(1),
+    }
+}
+
+/// Apply a single operation from a script to a list of directories.
+fn apply_op(words: &[String], current: Vec<PathBuf>) -> io::Result<Vec<PathBuf>> {
+    let mut ops = OpsOpt {
+        prepend: vec![],
+        append: vec![],
+        rm: vec![],
+        up: vec![],
+        dn: vec![],
+        force: false,
+    };
+
+    let (op, args) = match words.split_first() {
+        Some((op, args)) => (op.as_str(), args),
+        None => return Ok(current),
+    };
+    match (op, args) {
+        ("add", args) => {
+            let mut prepend = false;
+            let mut dirs = vec![];
+            for a in args {
+                match a.as_str() {
+                    "-p" | "--prepend" => prepend = true,
+                    "-f" | "--force" => ops.force = true,
+                    d => dirs.push(PathBuf::from(d)),
+                }
+            }
+            if dirs.is_empty() {
+                return Err(usage("add [-p] [-f] DIR..."));
+            }
+            match prepend {
+                true => ops.prepend = dirs,
+                false => ops.append = dirs,
+            }
+        }
+        ("rm", dirs) if !dirs.is_empty() => ops.rm = dirs.iter().map(PathBuf::from).collect(),
+        ("rm", _) => return Err(usage("rm DIR...")),
+        ("up", [dir, rest @ ..]) | ("dn", [dir, rest @ ..]) if rest.len() <= 1 => {
+            let usage_text = format!("{} DIR [JUMP]", op);
+            // a jump past either end stops there, so it doesn't need to fit exactly
+            let jump = parse_number(rest.first(), &usage_text)?.min(isize::MAX as usize) as isize;
+            let d = PathBuf::from(dir);
+            return match find_dir(&current, &d) {
+                Some(i) if op == "up" => Ok(move_dir(current, i, -jump)),
+                Some(i) => Ok(move_dir(current, i, jump)),
+                None => Err(not_found(&d)),
+            };
+        }
+        ("up", _) | ("dn", _) => return Err(usage(&format!("{} DIR [JUMP]", op))),
+        ("mv", [dir, index]) => {
+            // an index past the end moves the directory to the end
+            let index =
+                parse_number(Some(index), "mv DIR INDEX")?.min(current.len().saturating_sub(1));
+            let d = PathBuf::from(dir);
+            return match find_dir(&current, &d) {
+                Some(i) => Ok(move_dir(current, i, index as isize - i as isize)),
+                None => Err(not_found(&d)),
+            };
+        }
+        ("mv", _) => return Err(usage("mv DIR INDEX")),
+        ("clean", []) => return Ok(clean_dirs(current)),
+        ("clean", _) => return Err(usage("clean")),
+        (op, _) => {
+            return Err(PadError::InvalidInput(format!(
+            "Unknown operation `{}`. Expected one of `add`, `rm`, `up`, `dn`, `mv`, or `clean`.",
+            op
+        ))
+            .into())
+        }
+    }
+
+    ops.validate()?;
+    ops.apply(current)
+}
+
+/// Apply every operation in a script, in order, to a list of directories.
+///
+/// If any operation fails, nothing is applied, and the error is returned along
+/// with the number of the line it came from.
+fn apply_script(script: &str, current: Vec<PathBuf>) -> Result<Vec<PathBuf>, (usize, io::Error)> {
+    let mut vpath = current;
+    for (n, line) in script.lines().enumerate() {
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+        vpath = split_words(line)
+            .and_then(|words| apply_op(&words, vpath))
+            .map_err(|e| (n + 1, e))?;
+    }
+    Ok(vpath)
+}
+
+/// Apply a script of operations to `$PATH`, recording a single history entry.
+pub fn apply_batch(opts: &BatchOpt) -> io::Result<()> {
+    let script = match opts.read_script() {
+        Ok(s) => s,
+        Err(e) => {
+            if !opts.write.quiet {
+                eprintln!("{}", e);
+            }
+            return Err(e);
+        }
+    };
+    let vpath = match apply_script(&script, read_path()) {
+        Ok(v) => v,
+        Err((n, e)) => {
+            if !opts.write.quiet {
+                let line = script.lines().nth(n - 1).unwrap_or_default().trim();
+                eprintln!("Line {}: `{}`. {}", n, line, e);
+            }
+            return Err(e);
+        }
+    };
+
+    let result = combine_path_like(vpath).and_then(|p| replace_path(p, &opts.write));
+    if let Err(e) = &result {
+        if !opts.write.quiet {
+            eprintln!("{}", e);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::dirs;
+
+    #[test]
+    fn split_words_with_quotes() {
+        let observed = split_words(r#"add -f "/a b" '/c "d"' /e"#).unwrap();
+        assert_eq!(observed, vec!["add", "-f", "/a b", r#"/c "d""#, "/e"]);
+        assert!(split_words("rm '/a").is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn script_applied_in_order() {
+        let script = "\
+# set up
+add -f -p /new
+rm /b
+
+dn /new 2
+mv /d 0
+";
+        let observed = apply_script(script, dirs(&["/a", "/b", "/c", "/d"])).unwrap();
+        assert_eq!(observed, dirs(&["/d", "/a", "/c", "/new"]));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn large_jumps_stop_at_the_ends() {
+        let script = format!("up /c {}\ndn /a {}\n", usize::MAX, usize::MAX);
+        let observed = apply_script(&script, dirs(&["/a", "/b", "/c"])).unwrap();
+        assert_eq!(observed, dirs(&["/c", "/b", "/a"]));
+
+        let script = format!("mv /a {}\n", usize::MAX);
+        let observed = apply_script(&script, dirs(&["/a", "/b", "/c"])).unwrap();
+        assert_eq!(observed, dirs(&["/b", "/c", "/a"]));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn script_fails_atomically() {
+        let script = "add -f /new\nrm /pad-path/does/not/exist\n";
+        let (line, err) = apply_script(script, dirs(&["/a"])).unwrap_err();
+        assert_eq!(line, 2);
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+}
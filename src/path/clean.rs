@@ -1,18 +1,15 @@
 //! Clean up the `$PATH`.
 
-#[cfg(target_os = "windows")]
-use std::os::windows::ffi::OsStrExt;
-
 use super::{
-    read::{combine_path_like, read_path},
-    write::replace_path,
+    read::{combine_path_like, foreign_target, read_path},
+    target::Target,
+    write::{replace_path, WriteOpt},
 };
+use crate::{config::config, system::system};
 use clap::{crate_authors, Parser};
 use itertools::Itertools;
 use std::{
-    env::current_dir,
     ffi::OsString,
-    fs::canonicalize,
     io,
     path::{Path, PathBuf, MAIN_SEPARATOR},
 };
@@ -24,17 +21,12 @@ use std::{
     visible_alias = "dedup",
 )]
 pub struct CleanOpt {
-    /// Don't print warnings when modifying `$PATH`.
+    /// Replace empty and relative entries with the current directory, instead of warning about them
     #[clap(short, long)]
-    quiet: bool,
-
-    /// Add current `$PATH` to the history
-    #[clap(short = 'H', long)]
-    history: bool,
+    resolve_relative: bool,
 
-    /// Don't do anything, just preview what this command would do
-    #[clap(short = 'n', long = "dry-run")]
-    dry_run: bool,
+    #[clap(flatten)]
+    pub write: WriteOpt,
 }
 
 impl CleanOpt {
@@ -49,12 +41,20 @@ impl CleanOpt {
 /// No behaviour changes occur after cleaning the path, since we keep the first
 /// occurrence in its position and remove all latter occurrences.
 pub fn clean_path(opts: &CleanOpt) -> io::Result<()> {
-    let current_path = read_path();
+    let mut current_path = read_path();
+    for dir in current_path.iter_mut().filter(|d| is_relative_entry(d)) {
+        match opts.resolve_relative {
+            true => *dir = clean_dir_name(dir),
+            false if !opts.write.quiet => eprintln!("{}", relative_warning(dir)),
+            false => {}
+        }
+    }
+
     let newpath = clean_given_path(current_path)?;
-    match replace_path(newpath, opts.dry_run, opts.history, opts.quiet) {
+    match replace_path(newpath, &opts.write) {
         Ok(()) => Ok(()),
         Err(e) => {
-            if !opts.quiet {
+            if !opts.write.quiet {
                 eprintln!("Could not clean `$PATH`. {}", e);
             }
             Err(e)
@@ -64,39 +64,138 @@ pub fn clean_path(opts: &CleanOpt) -> io::Result<()> {
 
 /// Clean up a given list of directories by checking for duplicates and other errors.
 pub fn clean_given_path(dirs: Vec<PathBuf>) -> io::Result<OsString> {
-    // only keep existing and unique directories
-    let cleaned_dirs = dirs.into_iter().filter(|p| p.exists()).unique().collect();
+    combine_path_like(clean_dirs(dirs))
+}
 
-    combine_path_like(cleaned_dirs)
+/// Only keep the first occurrence of each directory that exists.
+///
+/// Empty and relative entries depend on the current directory when a command
+/// is run, not when `pad` is run, so they are always kept.
+pub fn clean_dirs(dirs: Vec<PathBuf>) -> Vec<PathBuf> {
+    // directories on another platform can only be told apart by their names
+    if let Some(t) = foreign_target() {
+        let mut kept: Vec<PathBuf> = vec![];
+        for d in dirs {
+            if !kept
+                .iter()
+                .any(|k| t.same_dir(&k.to_string_lossy(), &d.to_string_lossy()))
+            {
+                kept.push(d);
+            }
+        }
+        return kept;
+    }
+
+    let sys = system();
+    dirs.into_iter()
+        .filter(|p| p.is_relative() || sys.exists(p))
+        .unique()
+        .collect()
+}
+
+/// Describe why an empty or relative entry in `$PATH` is a security concern.
+pub fn relative_warning(dir: &Path) -> String {
+    let entry = match dir.as_os_str().is_empty() {
+        true => String::from("an empty entry"),
+        false => format!("the relative directory `{}`", dir.display()),
+    };
+    let warning = format!(
+        "Warning: `$PATH` contains {}, so commands are looked up relative to whichever directory you are in. Anyone who can write to that directory can run commands as you.",
+        entry
+    );
+
+    // the current directory on another platform is unknown
+    match foreign_target() {
+        Some(_) => warning,
+        None => format!(
+            "{} Use `pad clean --resolve-relative` to replace it with `{}`.",
+            warning,
+            clean_dir_name(dir).display()
+        ),
+    }
+}
+
+/// Whether a directory in `$PATH` is empty or relative, and so depends on the current directory.
+pub fn is_relative_entry(dir: &Path) -> bool {
+    match foreign_target() {
+        Some(t) => !t.is_absolute(&dir.to_string_lossy()),
+        None => dir.is_relative(),
+    }
+}
+
+/// Clean the name of a directory in `$PATH`.
+///
+/// Empty and relative entries are kept as they are, since they refer to the
+/// current directory when a command is run, unless the configuration says to
+/// resolve them.
+pub fn clean_entry(dir: &Path) -> PathBuf {
+    match dir.is_relative() && !config().resolve_relative && foreign_target().is_none() {
+        true if has_trailing_slash(dir) => trim_trailing_slashes(dir),
+        true => dir.to_path_buf(),
+        false => clean_dir_name(dir),
+    }
 }
 
 /// Clean directory names by removing trailing folder separator characters and
 /// converting to absolute paths
 pub fn clean_dir_name(dir: &Path) -> PathBuf {
-    let _cleaned_dir = match has_trailing_slash(&dir) {
-        true => {
-            let mut _temp_dir = dir
-                .to_string_lossy()
-                .trim_end_matches(MAIN_SEPARATOR)
-                .to_string();
-            PathBuf::from(_temp_dir)
-        }
+    // the current directory and symlinks on another platform are unknown
+    if let Some(t) = foreign_target() {
+        return PathBuf::from(t.trim_trailing_separators(&dir.to_string_lossy()));
+    }
+
+    // an empty entry in `$PATH` is the current directory
+    if dir.as_os_str().is_empty() {
+        return make_abs_path(Path::new("."));
+    }
+
+    let _cleaned_dir = match has_trailing_slash(dir) {
+        true => trim_trailing_slashes(dir),
         false => dir.to_path_buf(),
     };
     make_abs_path(&_cleaned_dir)
 }
 
+/// Remove trailing folder separator characters, keeping any bytes that aren't valid UTF-8.
+///
+/// The root directory keeps its separator, so that it doesn't become an empty entry.
+#[cfg(unix)]
+fn trim_trailing_slashes(dir: &Path) -> PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+
+    let bytes = dir.as_os_str().as_bytes();
+    let end = bytes
+        .iter()
+        .rposition(|b| *b != MAIN_SEPARATOR as u8)
+        .map_or(bytes.len().min(1), |i| i + 1);
+    PathBuf::from(std::ffi::OsStr::from_bytes(&bytes[..end]))
+}
+
+/// Remove trailing folder separator characters.
+#[cfg(not(unix))]
+fn trim_trailing_slashes(dir: &Path) -> PathBuf {
+    PathBuf::from(Target::host().trim_trailing_separators(&dir.to_string_lossy()))
+}
+
 /// Clean a list of directories
 pub fn clean_dirs_names<P: AsRef<Path>>(dirs: &[P]) -> Vec<PathBuf> {
     dirs.iter().map(|p| clean_dir_name(p.as_ref())).collect()
 }
 
 /// Force a PathBuf to be absolute, or make it absolute using the current directory
+///
+/// Symlinks are resolved unless disabled in the configuration.
 fn make_abs_path(p: &Path) -> PathBuf {
-    match canonicalize(p) {
-        Ok(p) => p,
-        Err(_) => {
-            let mut abs_dir = current_dir().unwrap();
+    let sys = system();
+    let canonical = match config().canonicalize {
+        true => sys.canonicalize(p).ok(),
+        false => None,
+    };
+
+    match canonical {
+        Some(p) => p,
+        None => {
+            let mut abs_dir = sys.current_dir().unwrap_or_default();
             abs_dir.push(p);
             abs_dir
         }
@@ -104,26 +203,15 @@ fn make_abs_path(p: &Path) -> PathBuf {
 }
 
 /// Check if a directory Path contains the trailing separator.
-#[cfg(target_os = "windows")]
-fn has_trailing_slash(p: &Path) -> bool {
-    let last = p.as_os_str().encode_wide().last();
-    // Windows can have '/' or '\' as its trailing character
-    last == Some(b'\\' as u16) || last == Some(b'/' as u16)
-}
-/// Check if a directory Path contains the trailing separator.
-#[cfg(target_os = "linux")]
-fn has_trailing_slash(p: &Path) -> bool {
-    p.to_string_lossy().as_bytes().last() == Some(&b'/')
-}
-/// Check if a directory Path contains the trailing separator.
-#[cfg(target_os = "macos")]
 fn has_trailing_slash(p: &Path) -> bool {
-    p.to_string_lossy().as_bytes().last() == Some(&b'/')
+    Target::host().has_trailing_separator(&p.to_string_lossy())
 }
 
 #[cfg(test)]
 mod tests {
     use crate::path::clean::make_abs_path;
+    #[cfg(unix)]
+    use crate::system::{memory::MemorySystem, with_system};
     use std::path::{Path, PathBuf};
 
     #[test]
@@ -137,9 +225,40 @@ mod tests {
     }
 
     #[test]
-    #[cfg(not(windows))]
+    #[cfg(unix)]
     fn remove_middle_relative() {
-        check_make_abs_path(Path::new("/etc/../etc"), PathBuf::from("/etc"));
+        with_system(MemorySystem::new().with_dir("/etc"), || {
+            check_make_abs_path(Path::new("/etc/../etc"), PathBuf::from("/etc"));
+        });
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn relative_entries_kept() {
+        use crate::path::{
+            clean::{clean_dirs, clean_entry},
+            dirs,
+        };
+
+        assert_eq!(clean_entry(Path::new("")), PathBuf::new());
+        assert_eq!(clean_entry(Path::new("bin/")), PathBuf::from("bin"));
+        assert_eq!(clean_entry(Path::new("/")), PathBuf::from("/"));
+
+        let observed = clean_dirs(dirs(&["", ".", "/pad-path/missing", ".", "/"]));
+        assert_eq!(observed, dirs(&["", ".", "/"]));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn non_utf8_names_kept() {
+        use crate::path::clean::clean_dir_name;
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        let dir = Path::new(OsStr::from_bytes(b"/pad-path/\xff//"));
+        assert_eq!(
+            clean_dir_name(dir).as_os_str().as_bytes(),
+            b"/pad-path/\xff"
+        );
     }
 
     #[test]
@@ -152,29 +271,21 @@ mod tests {
     }
 
     #[test]
-    #[cfg(target_os = "linux")]
+    #[cfg(unix)]
     fn relative_path_made_absolute() {
-        let pwd = PathBuf::from("/bin");
-        let parent = PathBuf::from("/");
-        let sibling = PathBuf::from("/etc");
-        let descendent = PathBuf::from("/bin/sh");
+        let sys = MemorySystem::new()
+            .with_dir("/etc")
+            .with_file("/usr/bin/sh", true)
+            .with_symlink("/bin", "usr/bin")
+            .with_cwd("/bin");
 
-        check_make_abs_path(&pwd.join(".."), parent);
-        check_make_abs_path(&pwd.join("../etc"), sibling);
-        check_make_abs_path(&pwd.join("../bin/sh"), descendent);
-    }
-
-    #[test]
-    #[cfg(target_os = "macos")]
-    fn relative_path_made_absolute() {
-        let pwd = PathBuf::from("/usr");
-        let parent = PathBuf::from("/");
-        let sibling = PathBuf::from("/Applications");
-        let descendent = PathBuf::from("/usr/bin");
-
-        check_make_abs_path(&pwd.join(".."), parent);
-        check_make_abs_path(&pwd.join("../Applications"), sibling);
-        check_make_abs_path(&pwd.join("../usr/bin"), descendent);
+        with_system(sys, || {
+            check_make_abs_path(Path::new(".."), PathBuf::from("/usr"));
+            check_make_abs_path(Path::new("../../etc"), PathBuf::from("/etc"));
+            check_make_abs_path(Path::new("sh"), PathBuf::from("/usr/bin/sh"));
+            // directories that don't exist are only made absolute
+            check_make_abs_path(Path::new("missing"), PathBuf::from("/bin/missing"));
+        });
     }
 
     #[test]
@@ -0,0 +1,241 @@
+//! Convert `$PATH` between the ways Windows, MSYS2, Cygwin, and WSL refer to
+//! Windows drives.
+//!
+//! Only the strings are rewritten, so a `$PATH` can be converted for any of
+//! them from any platform.
+
+use super::{read::read_raw_path, target::Target};
+use crate::{error::PadError, system::stdout};
+use clap::{crate_authors, Parser, ValueEnum};
+use std::io::{self, Write};
+
+/// A convention for referring to Windows drives in `$PATH`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Convention {
+    /// `C:\Tools\bin`, with directories separated by `;`
+    Windows,
+    /// `/c/Tools/bin`, as used by MSYS2 and Git Bash
+    Msys,
+    /// `/cygdrive/c/Tools/bin`
+    Cygwin,
+    /// `/mnt/c/Tools/bin`, as used by WSL
+    Wsl,
+}
+
+impl Convention {
+    /// The conventions that write drives as Unix directories, most specific first.
+    const UNIX: [Convention; 3] = [Convention::Cygwin, Convention::Wsl, Convention::Msys];
+
+    /// The platform whose `$PATH` conventions are followed.
+    fn target(&self) -> Target {
+        match self {
+            Convention::Windows => Target::Windows,
+            _ => Target::Unix,
+        }
+    }
+
+    /// What comes before the drive letter.
+    fn drive_prefix(&self) -> &'static str {
+        match self {
+            Convention::Windows => "",
+            Convention::Msys => "/",
+            Convention::Cygwin => "/cygdrive/",
+            Convention::Wsl => "/mnt/",
+        }
+    }
+
+    /// Split a directory into its drive letter and the folders within the drive,
+    /// if it is on a Windows drive.
+    fn parse<'a>(&self, dir: &'a str) -> Option<(char, Vec<&'a str>)> {
+        let rest = dir.strip_prefix(self.drive_prefix())?;
+        let mut chars = rest.chars();
+        let letter = chars.next().filter(char::is_ascii_alphabetic)?;
+        let rest = match self {
+            // `C:Tools` is relative to the current directory on `C:`
+            Convention::Windows => chars.as_str().strip_prefix(':')?,
+            _ => chars.as_str(),
+        };
+        if !(rest.is_empty() || rest.starts_with(self.target().dir_separators())) {
+            return None;
+        }
+
+        let folders = rest
+            .split(self.target().dir_separators())
+            .filter(|f| !f.is_empty())
+            .collect();
+        Some((letter, folders))
+    }
+
+    /// Write a directory on a Windows drive.
+    fn format(&self, letter: char, folders: &[&str]) -> String {
+        match self {
+            Convention::Windows => {
+                format!("{}:\\{}", letter.to_ascii_uppercase(), folders.join("\\"))
+            }
+            _ => {
+                let mut dir = format!("{}{}", self.drive_prefix(), letter.to_ascii_lowercase());
+                for f in folders {
+                    dir.push('/');
+                    dir.push_str(f);
+                }
+                dir
+            }
+        }
+    }
+
+    /// Guess the convention of a `$PATH` that is written for Windows.
+    ///
+    /// The Unix conventions can't be told apart from the whole `$PATH`, so
+    /// each directory is checked against all of them instead.
+    fn detect(path: &str) -> Option<Convention> {
+        let starts_with_drive = Convention::Windows
+            .parse(path.split(';').next().unwrap_or_default())
+            .is_some();
+        match path.contains(';') || starts_with_drive {
+            true => Some(Convention::Windows),
+            false => None,
+        }
+    }
+}
+
+#[derive(Debug, Parser)]
+#[clap(
+    about = "Convert `$PATH` between Windows, MSYS2, Cygwin, and WSL",
+    author = crate_authors!(),
+)]
+pub struct ConvertOpt {
+    /// Convention to convert to
+    #[clap(short, long, value_enum)]
+    to: Convention,
+
+    /// Convention to convert from. Guessed from the `$PATH` if not given.
+    #[clap(short, long, value_enum)]
+    from: Option<Convention>,
+
+    /// Don't print warnings.
+    #[clap(short, long)]
+    quiet: bool,
+}
+
+impl ConvertOpt {
+    /// Validate options
+    pub fn validate(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Convert each directory in `$PATH` that is on a Windows drive.
+///
+/// Other directories are kept as they are, and returned separately so they can
+/// be reported.
+pub fn convert(
+    path: &str,
+    from: Option<Convention>,
+    to: Convention,
+) -> io::Result<(String, Vec<String>)> {
+    let from = from.or_else(|| Convention::detect(path));
+    let (target, candidates) = match from {
+        Some(c) => (c.target(), vec![c]),
+        None => (Target::Unix, Vec::from(Convention::UNIX)),
+    };
+
+    let mut unchanged = vec![];
+    let dirs: Vec<String> = target
+        .split(path)
+        .into_iter()
+        .map(|d| {
+            match candidates.iter().find_map(|c| c.parse(&d)) {
+                Some((letter, folders)) => to.format(letter, &folders),
+                None => {
+                    // empty entries mean the same thing everywhere
+                    if !d.is_empty() && from != Some(to) {
+                        unchanged.push(d.clone());
+                    }
+                    d
+                }
+            }
+        })
+        .collect();
+
+    Ok((to.target().join(&dirs)?, unchanged))
+}
+
+/// Print the converted `$PATH`.
+pub fn convert_path(opts: &ConvertOpt) -> io::Result<()> {
+    let path = read_raw_path().unwrap_or_default();
+
+    let result = path
+        .to_str()
+        .ok_or_else(|| {
+            io::Error::from(PadError::InvalidPath(String::from(
+                "It cannot be converted because it is not valid UTF-8.",
+            )))
+        })
+        .and_then(|p| convert(p, opts.from, opts.to));
+
+    match result {
+        Ok((converted, unchanged)) => {
+            if !opts.quiet {
+                for d in unchanged {
+                    eprintln!(
+                        "Directory `{}` is not on a Windows drive, so it was kept as it is.",
+                        d
+                    );
+                }
+            }
+            writeln!(stdout(), "{}", converted)
+        }
+        Err(e) => {
+            if !opts.quiet {
+                eprintln!("{}", e);
+            }
+            Err(e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn between_all_conventions() {
+        let windows = r"C:\Tools\bin;d:\;C:\Windows";
+        let converted = |path, from, to| convert(path, from, to).unwrap().0;
+
+        assert_eq!(
+            converted(windows, None, Convention::Msys),
+            "/c/Tools/bin:/d:/c/Windows"
+        );
+        assert_eq!(
+            converted(windows, None, Convention::Cygwin),
+            "/cygdrive/c/Tools/bin:/cygdrive/d:/cygdrive/c/Windows"
+        );
+        assert_eq!(
+            converted(windows, None, Convention::Wsl),
+            "/mnt/c/Tools/bin:/mnt/d:/mnt/c/Windows"
+        );
+        assert_eq!(
+            converted(
+                "/mnt/c/Tools/bin:/cygdrive/d/:/c/Windows",
+                None,
+                Convention::Windows
+            ),
+            r"C:\Tools\bin;D:\;C:\Windows"
+        );
+    }
+
+    #[test]
+    fn other_directories_are_kept() {
+        let (converted, unchanged) =
+            convert("/usr/bin:/mnt/data/bin::/mnt/c", None, Convention::Windows).unwrap();
+        assert_eq!(converted, r"/usr/bin;/mnt/data/bin;;C:\");
+        assert_eq!(unchanged, vec!["/usr/bin", "/mnt/data/bin"]);
+
+        // only the given convention is recognized
+        let (converted, _) =
+            convert("/c/bin:/mnt/c/bin", Some(Convention::Msys), Convention::Wsl).unwrap();
+        assert_eq!(converted, "/mnt/c/bin:/mnt/c/bin");
+        assert!(convert(r"C:bin;C:\bin", None, Convention::Msys).is_err());
+    }
+}
@@ -0,0 +1,211 @@
+//! Compare two versions of `$PATH`.
+
+use super::{list::path_of, read::split_raw_path};
+use crate::{error::PadError, system::stdout};
+use clap::{crate_authors, Parser};
+use std::{
+    io::{self, Write},
+    path::PathBuf,
+};
+
+#[derive(Debug, Parser)]
+#[clap(
+    about = "Compare the `$PATH` of running processes",
+    author = crate_authors!(),
+)]
+pub struct DiffOpt {
+    /// Process to compare. Give this twice to compare two processes, or once
+    /// to compare this process with another one.
+    #[clap(long, required = true)]
+    pid: Vec<u32>,
+
+    /// Don't print warnings.
+    #[clap(short, long)]
+    quiet: bool,
+}
+
+impl DiffOpt {
+    /// Validate options
+    pub fn validate(&self) -> io::Result<()> {
+        if self.pid.len() > 2 {
+            let err: io::Error =
+                PadError::InvalidInput(String::from("Only two processes can be compared at once."))
+                    .into();
+
+            if !self.quiet {
+                eprintln!("{}", err);
+            }
+
+            return Err(err);
+        }
+
+        Ok(())
+    }
+}
+
+/// The differences between two versions of `$PATH`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct PathDiff {
+    /// Directories in the earlier `$PATH`
+    pub before: Vec<PathBuf>,
+    /// Directories in the later `$PATH`
+    pub after: Vec<PathBuf>,
+}
+
+impl PathDiff {
+    pub fn new(before: Vec<PathBuf>, after: Vec<PathBuf>) -> PathDiff {
+        PathDiff { before, after }
+    }
+
+    /// Directories that are only in the later `$PATH`, in order of priority.
+    pub fn added(&self) -> Vec<&PathBuf> {
+        self.after
+            .iter()
+            .filter(|d| !self.before.contains(d))
+            .collect()
+    }
+
+    /// Directories that are only in the earlier `$PATH`, in order of priority.
+    pub fn removed(&self) -> Vec<&PathBuf> {
+        self.before
+            .iter()
+            .filter(|d| !self.after.contains(d))
+            .collect()
+    }
+
+    /// Directories in both versions whose position relative to the others changed.
+    ///
+    /// The directories that kept their order are the longest sequence common
+    /// to both versions, so moving one directory only reports that one, however
+    /// far it moved.
+    pub fn moved(&self) -> Vec<&PathBuf> {
+        let kept_before: Vec<&PathBuf> = self
+            .before
+            .iter()
+            .filter(|d| self.after.contains(d))
+            .collect();
+        let kept_after: Vec<&PathBuf> = self
+            .after
+            .iter()
+            .filter(|d| self.before.contains(d))
+            .collect();
+
+        // longest[i][j] is the length of the longest common subsequence of
+        // `kept_before[i..]` and `kept_after[j..]`
+        let (n, m) = (kept_before.len(), kept_after.len());
+        let mut longest = vec![vec![0; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                longest[i][j] = match kept_before[i] == kept_after[j] {
+                    true => longest[i + 1][j + 1] + 1,
+                    false => longest[i + 1][j].max(longest[i][j + 1]),
+                };
+            }
+        }
+
+        // on a tie, keep the earlier directory where it was, so that moving a
+        // directory up reports that directory rather than the ones it passed
+        let mut moved = vec![];
+        let (mut i, mut j) = (0, 0);
+        while j < m {
+            if i < n && kept_before[i] == kept_after[j] {
+                i += 1;
+                j += 1;
+            } else if i < n && longest[i + 1][j] > longest[i][j + 1] {
+                i += 1;
+            } else {
+                moved.push(kept_after[j]);
+                j += 1;
+            }
+        }
+        moved
+    }
+
+    /// The directories added before and after the earlier `$PATH`.
+    ///
+    /// This is `None` if the later `$PATH` can't be made by only adding
+    /// directories to the beginning and end of the earlier one.
+    pub fn prepended_appended(&self) -> Option<(&[PathBuf], &[PathBuf])> {
+        if self.before.is_empty() {
+            return Some((&self.after, &[]));
+        }
+
+        let n = self.before.len();
+        (0..=self.after.len().checked_sub(n)?)
+            .find(|&i| self.after[i..(i + n)] == self.before[..])
+            .map(|i| (&self.after[..i], &self.after[(i + n)..]))
+    }
+
+    /// Describe each change on its own line, marked with `+`, `-`, or `~` for
+    /// added, removed, and moved directories.
+    pub fn summary(&self) -> Vec<String> {
+        let marked = |mark: &str, dirs: Vec<&PathBuf>| -> Vec<String> {
+            dirs.iter()
+                .map(|d| format!("{} {}", mark, d.display()))
+                .collect()
+        };
+
+        let mut lines = marked("+", self.added());
+        lines.extend(marked("-", self.removed()));
+        lines.extend(marked("~", self.moved()));
+        lines
+    }
+}
+
+/// Print the differences between the `$PATH` of two processes.
+pub fn diff_paths(opts: &DiffOpt) -> io::Result<()> {
+    let (before, after) = match opts.pid[..] {
+        [a, b] => (Some(a), Some(b)),
+        _ => (None, opts.pid.first().copied()),
+    };
+
+    let split = |pid| path_of(pid).map(|p| split_raw_path(&p));
+    let diff = match split(before).and_then(|b| Ok(PathDiff::new(b, split(after)?))) {
+        Ok(d) => d,
+        Err(e) => {
+            if !opts.quiet {
+                eprintln!("{}", e);
+            }
+            return Err(e);
+        }
+    };
+
+    for line in diff.summary() {
+        writeln!(stdout(), "{}", line)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::dirs;
+
+    #[test]
+    fn changes_around_earlier_path() {
+        let diff = PathDiff::new(dirs(&["/a", "/b"]), dirs(&["/x", "/a", "/b", "/y"]));
+        assert_eq!(
+            diff.prepended_appended(),
+            Some((&dirs(&["/x"])[..], &dirs(&["/y"])[..]))
+        );
+        assert_eq!(diff.summary(), vec!["+ /x", "+ /y"]);
+
+        let diff = PathDiff::new(dirs(&["/a", "/b", "/c"]), dirs(&["/c", "/a", "/x"]));
+        assert_eq!(diff.prepended_appended(), None);
+        assert_eq!(diff.summary(), vec!["+ /x", "- /b", "~ /c"]);
+    }
+
+    #[test]
+    fn only_moved_dirs_are_reported() {
+        let before = dirs(&["/a", "/b", "/c", "/d"]);
+        let moved = |after: &[&str]| -> Vec<PathBuf> {
+            let diff = PathDiff::new(before.clone(), dirs(after));
+            diff.moved().into_iter().cloned().collect()
+        };
+
+        assert_eq!(moved(&["/d", "/a", "/b", "/c"]), dirs(&["/d"]));
+        assert_eq!(moved(&["/x", "/a", "/b", "/c", "/d"]), dirs(&[]));
+        assert_eq!(moved(&["/b", "/c", "/d", "/a"]), dirs(&["/a"]));
+        assert_eq!(moved(&["/b", "/a", "/d", "/c"]), dirs(&["/b", "/d"]));
+    }
+}
@@ -0,0 +1,224 @@
+//! Edit `$PATH` in a text editor, one directory per line.
+
+use super::{
+    add::check_dirs_exist,
+    clean::clean_entry,
+    list::{inspect_path, PathEntry},
+    read::{combine_path_like, read_raw_path},
+    write::{replace_path, WriteOpt},
+};
+use crate::{error::PadError, system::system};
+use clap::{crate_authors, Parser};
+use std::{
+    env::temp_dir,
+    ffi::OsString,
+    fs::{read_to_string, remove_file, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::{self, Command, Stdio},
+};
+
+/// Instructions written at the top of the file being edited.
+const HEADER: &str = "\
+# Edit `$PATH` below, one directory per line, from highest to lowest priority.
+# Reorder, delete, or add lines as needed. Lines starting with `#` are ignored.
+# Save the file unchanged, or with no directories, to abort.
+# An empty entry, which means the current directory, is written as `\"\"`.
+";
+
+/// How an empty entry in `$PATH` is written, since blank lines are ignored.
+const EMPTY_ENTRY: &str = "\"\"";
+
+#[derive(Debug, Parser)]
+#[clap(
+    about = "Edit `$PATH` in your `$EDITOR`",
+    author = crate_authors!(),
+)]
+pub struct EditOpt {
+    /// Forcefully add directories that don't necessarily exist.
+    #[clap(short, long)]
+    force: bool,
+
+    #[clap(flatten)]
+    pub write: WriteOpt,
+}
+
+impl EditOpt {
+    /// Validate options
+    pub fn validate(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Describe any problems with a directory in `$PATH`.
+fn status(entry: &PathEntry, entries: &[PathEntry]) -> Vec<String> {
+    let mut status = vec![];
+    if entry.is_relative {
+        status.push(String::from("relative to the current directory"));
+    }
+    if !entry.exists {
+        status.push(String::from("missing"));
+    } else if !entry.is_dir {
+        status.push(String::from("not a directory"));
+    }
+    if let Some(e) = entry.duplicate_of.and_then(|i| entries.get(i)) {
+        status.push(format!("duplicate of `{}`", e.raw.display()));
+    }
+    for e in entry.shadows.iter().filter_map(|i| entries.get(*i)) {
+        status.push(format!("shadows commands in `{}`", e.raw.display()));
+    }
+    status
+}
+
+/// Write the directories of `$PATH` as lines of text, with comments describing their status.
+fn render(entries: &[PathEntry]) -> String {
+    let mut contents = String::from(HEADER);
+    for e in entries {
+        contents.push('\n');
+        let status = status(e, entries);
+        if !status.is_empty() {
+            contents.push_str(&format!("# {}\n", status.join(", ")));
+        }
+        match e.raw.as_os_str().is_empty() {
+            true => contents.push_str(EMPTY_ENTRY),
+            false => contents.push_str(&e.raw.to_string_lossy()),
+        }
+    }
+    contents.push('\n');
+    contents
+}
+
+/// Read the directories back from the edited text, ignoring comments and blank lines.
+fn parse(contents: &str) -> Vec<PathBuf> {
+    contents
+        .lines()
+        .map(|l| l.trim_end_matches('\r'))
+        .filter(|l| !l.trim().is_empty() && !l.trim_start().starts_with('#'))
+        .map(|l| match l == EMPTY_ENTRY {
+            true => PathBuf::new(),
+            false => PathBuf::from(l),
+        })
+        .collect()
+}
+
+/// Ensure that every directory can be written as text without losing any bytes.
+fn check_utf8(entries: &[PathEntry]) -> io::Result<()> {
+    match entries.iter().find(|e| e.raw.to_str().is_none()) {
+        Some(e) => Err(PadError::InvalidPath(format!(
+            "Directory `{}` is not valid UTF-8, so it can't be edited as text. Remove it with `pad rm` first.",
+            e.raw.display()
+        ))
+        .into()),
+        None => Ok(()),
+    }
+}
+
+/// The command used to edit files, falling back to a platform default.
+fn editor() -> OsString {
+    let sys = system();
+    sys.var_os("VISUAL")
+        .filter(|e| !e.is_empty())
+        .or_else(|| sys.var_os("EDITOR").filter(|e| !e.is_empty()))
+        .unwrap_or_else(|| match cfg!(windows) {
+            true => OsString::from("notepad"),
+            false => OsString::from("vi"),
+        })
+}
+
+/// Open a file in the user's editor and wait for it to close.
+fn open_in_editor(file: &Path) -> io::Result<()> {
+    // editors like `code --wait` are given with their arguments
+    let editor = editor();
+    let editor = editor.to_string_lossy();
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+
+    // STDOUT is where the new `$PATH` goes, so the editor draws on STDERR
+    let status = Command::new(program)
+        .args(words)
+        .arg(file)
+        .stdout(Stdio::from(io::stderr()))
+        .status()
+        .map_err(|e| PadError::CannotRun(editor.to_string(), e))?;
+
+    // exiting with an error, like `:cq` in Vim, is how editors abandon changes
+    match status.success() {
+        true => Ok(()),
+        false => Err(PadError::Cancelled.into()),
+    }
+}
+
+/// Let the user edit a file, returning its contents afterwards.
+fn edit_contents(contents: &str) -> io::Result<String> {
+    let file = temp_dir().join(format!("pad-path-{}.txt", process::id()));
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&file)?
+        .write_all(contents.as_bytes())?;
+
+    let edited = open_in_editor(&file).and_then(|_| read_to_string(&file));
+    let _ = remove_file(&file);
+    edited
+}
+
+/// Edit `$PATH` in a text editor.
+pub fn edit_path(opts: &EditOpt) -> io::Result<()> {
+    let entries = inspect_path(&read_raw_path().unwrap_or_default(), true);
+    let contents = render(&entries);
+
+    let result = check_utf8(&entries)
+        .and_then(|_| edit_contents(&contents))
+        .and_then(|edited| {
+            let dirs = parse(&edited);
+            if edited == contents || dirs.is_empty() {
+                return Err(PadError::Cancelled.into());
+            }
+
+            // directories already in `$PATH` are kept, even if they don't exist
+            if !opts.force {
+                let added: Vec<PathBuf> = dirs
+                    .iter()
+                    .filter(|d| !entries.iter().any(|e| e.normalized == clean_entry(d)))
+                    .cloned()
+                    .collect();
+                check_dirs_exist(&added)?;
+            }
+
+            replace_path(combine_path_like(dirs)?, &opts.write)
+        });
+
+    if let Err(e) = &result {
+        if !opts.write.quiet {
+            eprintln!("{}", e);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::dirs;
+
+    #[test]
+    #[cfg(unix)]
+    fn parse_skips_comments_and_blank_lines() {
+        let edited = "# header\n\n/a\n# missing\n/b\r\n   \n/c d\n\"\"\n";
+        assert_eq!(parse(edited), dirs(&["/a", "/b", "/c d", ""]));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn non_utf8_dirs_refused() {
+        use crate::system::{memory::MemorySystem, with_system};
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        with_system(MemorySystem::new(), || {
+            assert!(check_utf8(&inspect_path(OsStr::new("/a:/b"), false)).is_ok());
+            let entries = inspect_path(OsStr::from_bytes(b"/a:/b\xff"), false);
+            let code = PadError::from(check_utf8(&entries).unwrap_err()).exit_code();
+            assert_eq!(code, 7);
+        });
+    }
+}
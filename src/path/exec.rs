@@ -0,0 +1,193 @@
+//! Run a command or a subshell with a modified `$PATH`.
+
+use super::{clean::clean_given_path, ops::OpsOpt, read::read_path};
+use crate::{error::PadError, system::system};
+use clap::{crate_authors, Parser};
+use std::{ffi::OsString, io, process::Command};
+
+/// Environment variable set inside a subshell started by `pad shell`.
+///
+/// Its value is the number of nested subshells, so that it can be shown in a prompt.
+pub const SUBSHELL_ENV_VAR: &str = "PAD_PATH_SHELL";
+
+#[derive(Debug, Parser)]
+#[clap(
+    about = "Run a command with a modified `$PATH`",
+    author = crate_authors!(),
+)]
+pub struct ExecOpt {
+    #[clap(flatten)]
+    ops: OpsOpt,
+
+    /// Don't print warnings when modifying `$PATH`.
+    #[clap(short, long)]
+    quiet: bool,
+
+    /// Command to run, and its arguments
+    #[clap(last = true, required = true, value_name = "CMD")]
+    cmd: Vec<OsString>,
+}
+
+impl ExecOpt {
+    /// Validate options
+    pub fn validate(&self) -> io::Result<()> {
+        if let Err(e) = self.ops.validate() {
+            if !self.quiet {
+                eprintln!("{}", e);
+            }
+
+            return Err(e);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Parser)]
+#[clap(
+    about = "Start a subshell with a modified `$PATH`",
+    author = crate_authors!(),
+)]
+pub struct ShellOpt {
+    #[clap(flatten)]
+    ops: OpsOpt,
+
+    /// Don't print warnings when modifying `$PATH`.
+    #[clap(short, long)]
+    quiet: bool,
+}
+
+impl ShellOpt {
+    /// Validate options
+    pub fn validate(&self) -> io::Result<()> {
+        if let Err(e) = self.ops.validate() {
+            if !self.quiet {
+                eprintln!("{}", e);
+            }
+
+            return Err(e);
+        }
+
+        Ok(())
+    }
+}
+
+/// Compute the modified `$PATH`, printing any errors.
+fn modified_path(ops: &OpsOpt, quiet: bool) -> io::Result<OsString> {
+    match ops.apply(read_path()).and_then(clean_given_path) {
+        Ok(p) => Ok(p),
+        Err(e) => {
+            if !quiet {
+                eprintln!("{}", e);
+            }
+
+            Err(e)
+        }
+    }
+}
+
+/// Run the command with the modified `$PATH`.
+///
+/// On Unix, this process is replaced by the command, so its exit status and
+/// any signals it receives are exactly those of the command.
+pub fn exec_with_path(opts: &ExecOpt) -> io::Result<()> {
+    let newpath = modified_path(&opts.ops, opts.quiet)?;
+    let mut cmd = Command::new(&opts.cmd[0]);
+    cmd.args(&opts.cmd[1..]).env("PATH", newpath);
+
+    let err: io::Error = PadError::CannotRun(opts.cmd[0].to_string_lossy().into(), run(cmd)).into();
+    if !opts.quiet {
+        eprintln!("{}", err);
+    }
+    Err(err)
+}
+
+/// Start the user's shell with the modified `$PATH`.
+///
+/// Exiting the subshell returns to the original shell, with its `$PATH` untouched.
+pub fn spawn_shell(opts: &ShellOpt) -> io::Result<()> {
+    let newpath = modified_path(&opts.ops, opts.quiet)?;
+    let program = user_shell();
+    let depth = system()
+        .var_os(SUBSHELL_ENV_VAR)
+        .and_then(|d| d.to_str().and_then(|d| d.parse::<u32>().ok()))
+        .map_or(1, |d| d + 1);
+
+    let mut cmd = Command::new(&program);
+    cmd.env("PATH", newpath)
+        .env(SUBSHELL_ENV_VAR, depth.to_string());
+
+    let err: io::Error = PadError::CannotRun(program.to_string_lossy().into(), run(cmd)).into();
+    if !opts.quiet {
+        eprintln!("{}", err);
+    }
+    Err(err)
+}
+
+/// Find the user's preferred shell.
+#[cfg(unix)]
+fn user_shell() -> OsString {
+    system()
+        .var_os("SHELL")
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| OsString::from("/bin/sh"))
+}
+
+/// Find the user's preferred shell.
+#[cfg(not(unix))]
+fn user_shell() -> OsString {
+    system()
+        .var_os("COMSPEC")
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| OsString::from("cmd.exe"))
+}
+
+/// Replace this process with the command.
+///
+/// This only returns if the command could not be run.
+#[cfg(unix)]
+fn run(mut cmd: Command) -> io::Error {
+    use std::os::unix::process::CommandExt;
+
+    cmd.exec()
+}
+
+/// Run the command and exit with its exit status.
+///
+/// This only returns if the command could not be run.
+#[cfg(not(unix))]
+fn run(mut cmd: Command) -> io::Error {
+    match cmd.status() {
+        Ok(status) => std::process::exit(status.code().unwrap_or(1)),
+        Err(e) => e,
+    }
+}
+
+// running a command replaces the test process, so only failures are tested
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::system::{memory::MemorySystem, with_system};
+
+    /// Run `pad exec` with the given arguments, and get the exit code of its error.
+    fn exit_code(args: &[&str]) -> u8 {
+        let opts = ExecOpt::parse_from(["exec"].iter().chain(args));
+        let sys = MemorySystem::new().with_path("/usr/bin");
+        let err = with_system(sys, || exec_with_path(&opts)).unwrap_err();
+        PadError::from(err).exit_code()
+    }
+
+    #[test]
+    fn commands_that_cannot_run_fail_like_a_shell() {
+        use std::{env::temp_dir, fs::write};
+
+        assert_eq!(exit_code(&["-q", "--", "/pad-path/nonexistent"]), 127);
+
+        // a file without permission to execute it
+        let file = temp_dir().join(format!("pad-path-exec-{}", std::process::id()));
+        write(&file, "").unwrap();
+        let code = exit_code(&["-q", "--", &file.to_string_lossy()]);
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(code, 126);
+    }
+}
@@ -0,0 +1,30 @@
+//! Find executables within the directories of `$PATH`.
+
+use crate::system::system;
+use std::path::{Path, PathBuf};
+
+/// Find the first executable with a given name in a list of directories.
+pub fn find_command(name: &str, dirs: &[PathBuf]) -> Option<PathBuf> {
+    let sys = system();
+    dirs.iter().map(|d| d.join(name)).find(|p| {
+        if sys.is_executable(p) {
+            return true;
+        }
+        // on Windows, the extension is left off of commands
+        cfg!(windows) && sys.is_executable(&p.with_extension("exe"))
+    })
+}
+
+/// List the executables directly within a directory, sorted by name.
+pub fn list_executables(dir: &Path) -> Vec<PathBuf> {
+    let sys = system();
+    let mut exes: Vec<PathBuf> = match sys.read_dir(dir) {
+        Ok(entries) => entries
+            .into_iter()
+            .filter(|p| sys.is_executable(p))
+            .collect(),
+        Err(_) => vec![],
+    };
+    exes.sort();
+    exes
+}
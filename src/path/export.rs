@@ -0,0 +1,378 @@
+//! Export `$PATH` in the formats read by system configuration files, CI
+//! services, and container images.
+
+use super::{diff::PathDiff, history::get_nth_last_revision, read::read_raw_path, target::Target};
+use crate::{
+    error::PadError,
+    system::{stdout, system},
+};
+use clap::{crate_authors, Parser, ValueEnum};
+use std::{
+    ffi::OsString,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// Configuration file formats that `$PATH` can be exported to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// systemd `environment.d` configuration, like `~/.config/environment.d/path.conf`
+    EnvironmentD,
+    /// `/etc/environment`, as read by `pam_env`
+    EtcEnvironment,
+    /// `.env` files, as read by `dotenv` libraries and Docker Compose
+    Dotenv,
+    /// `~/.pam_environment`
+    Pam,
+    /// Lines for GitHub Actions' `$GITHUB_PATH` file, with the directories added since the baseline
+    Github,
+    /// GitLab CI `variables:`, relative to the baseline
+    Gitlab,
+    /// Dockerfile `ENV` instruction, relative to the baseline
+    Dockerfile,
+}
+
+impl ExportFormat {
+    /// Whether the format describes changes relative to a baseline `$PATH`.
+    fn is_relative(&self) -> bool {
+        matches!(
+            self,
+            ExportFormat::Github | ExportFormat::Gitlab | ExportFormat::Dockerfile
+        )
+    }
+}
+
+#[derive(Debug, Parser)]
+#[clap(
+    about = "Export `$PATH` for system configuration files",
+    author = crate_authors!(),
+)]
+pub struct ExportOpt {
+    /// Format to export to
+    #[clap(short, long, value_enum)]
+    format: ExportFormat,
+
+    /// `$PATH` that the CI formats are relative to.
+    /// Defaults to the `$PATH` before the most recent change in the history.
+    #[clap(long, value_name = "PATH")]
+    baseline: Option<OsString>,
+
+    /// Write to this file instead of STDOUT. `$GITHUB_PATH` lines are appended to it.
+    #[clap(short, long, value_name = "FILE")]
+    output: Option<PathBuf>,
+
+    /// Don't print warnings.
+    #[clap(short, long)]
+    quiet: bool,
+}
+
+impl ExportOpt {
+    /// Validate options
+    pub fn validate(&self) -> io::Result<()> {
+        if self.baseline.is_some() && !self.format.is_relative() {
+            let err: io::Error = PadError::InvalidInput(String::from("`--baseline` can only be used with the `github`, `gitlab`, and `dockerfile` formats.")).into();
+
+            if !self.quiet {
+                eprintln!("{}", err);
+            }
+
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    /// The `$PATH` that changes are relative to.
+    fn baseline(&self) -> io::Result<OsString> {
+        match &self.baseline {
+            Some(b) => Ok(b.clone()),
+            None => get_nth_last_revision(1),
+        }
+    }
+}
+
+/// Error for a `$PATH` that can't be written in a format.
+fn unrepresentable(format: ExportFormat, reason: &str) -> io::Error {
+    PadError::InvalidPath(format!(
+        "It cannot be exported as `{}` because it {}.",
+        format.to_possible_value().unwrap().get_name(),
+        reason
+    ))
+    .into()
+}
+
+/// Escape characters in a string with a backslash.
+fn escape(s: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if special.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Format a line that sets `$PATH` in a configuration file.
+///
+/// The target is only used to split `$PATH` for the formats that describe changes.
+pub fn export_line(path: &str, format: ExportFormat, target: Target) -> io::Result<String> {
+    // none of the formats can continue a value onto another line
+    if path.contains(['\n', '\r']) {
+        return Err(unrepresentable(format, "contains a line break"));
+    }
+
+    match format {
+        // `$` would start a variable reference, so everything special is escaped
+        ExportFormat::EnvironmentD => {
+            Ok(format!("PATH=\"{}\"", escape(path, &['\\', '"', '$', '`'])))
+        }
+        // surrounding quotes are removed, but nothing within them is escaped
+        ExportFormat::EtcEnvironment => match (path.contains('"'), path.contains('\'')) {
+            (false, _) => Ok(format!("PATH=\"{}\"", path)),
+            (true, false) => Ok(format!("PATH='{}'", path)),
+            (true, true) => Err(unrepresentable(
+                format,
+                "contains both single and double quotes",
+            )),
+        },
+        // single quoted values are taken literally, double quoted ones are expanded
+        ExportFormat::Dotenv => match path.contains('\'') {
+            false => Ok(format!("PATH='{}'", path)),
+            true => Ok(format!("PATH=\"{}\"", escape(path, &['\\', '"', '$', '`']))),
+        },
+        // both `${VAR}` and `@{ITEM}` are expanded
+        ExportFormat::Pam => Ok(format!(
+            "PATH DEFAULT=\"{}\"",
+            escape(path, &['\\', '"', '$', '@'])
+        )),
+        // without a baseline, every directory is new
+        _ => {
+            let diff = PathDiff::new(vec![], split_like(path, &[], target));
+            export_relative(path, &diff, format, target)
+        }
+    }
+}
+
+/// Split a `$PATH` for a target platform.
+///
+/// Directories are spelled the same way as in the baseline when the target
+/// considers them to be the same, like `C:\Tools` and `c:/tools/` on Windows.
+fn split_like(path: &str, baseline: &[PathBuf], target: Target) -> Vec<PathBuf> {
+    target
+        .split(path)
+        .iter()
+        .map(|d| {
+            baseline
+                .iter()
+                .find(|b| target.same_dir(&b.to_string_lossy(), d))
+                .cloned()
+                .unwrap_or_else(|| PathBuf::from(d))
+        })
+        .collect()
+}
+
+/// Join directories into a `$PATH`-like string, around a reference to the previous `$PATH`.
+///
+/// If the directories can't be added around the previous `$PATH`, all of the
+/// new `$PATH` is used instead.
+fn join_around(
+    diff: &PathDiff,
+    previous: &str,
+    target: Target,
+    escape_dir: impl Fn(&str) -> String,
+) -> io::Result<String> {
+    let join = |dirs: &[PathBuf]| -> Vec<String> {
+        dirs.iter()
+            .map(|d| escape_dir(&d.to_string_lossy()))
+            .collect()
+    };
+
+    match diff.prepended_appended() {
+        Some((prepended, appended)) => {
+            let mut parts = join(prepended);
+            parts.push(String::from(previous));
+            parts.extend(join(appended));
+            target.join(&parts)
+        }
+        None => target.join(&join(&diff.after)),
+    }
+}
+
+/// Format the changes from a baseline `$PATH` for a CI service or container image.
+fn export_relative(
+    path: &str,
+    diff: &PathDiff,
+    format: ExportFormat,
+    target: Target,
+) -> io::Result<String> {
+    if path.contains(['\n', '\r']) {
+        return Err(unrepresentable(format, "contains a line break"));
+    }
+
+    match format {
+        // each line is prepended to `$PATH` in turn, so the highest priority comes last
+        ExportFormat::Github => match diff.prepended_appended() {
+            Some((prepended, [])) => Ok(prepended
+                .iter()
+                .rev()
+                .map(|d| d.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("\n")),
+            _ => Err(unrepresentable(
+                format,
+                "does more than add directories to the beginning of the baseline",
+            )),
+        },
+        // GitLab expands `$VAR` in variables, and `$$` is a literal `$`
+        ExportFormat::Gitlab => Ok(format!(
+            "variables:\n  PATH: \"{}\"",
+            join_around(diff, "${PATH}", target, |d| escape(
+                &d.replace('$', "$$"),
+                &['\\', '"']
+            ))?
+        )),
+        ExportFormat::Dockerfile => Ok(format!(
+            "ENV PATH=\"{}\"",
+            join_around(diff, "${PATH}", target, |d| escape(d, &['\\', '"', '$']))?
+        )),
+        _ => export_line(path, format, target),
+    }
+}
+
+/// Write exported lines to a file, or print them.
+fn output(contents: &str, file: Option<&Path>, append: bool) -> io::Result<()> {
+    let line = format!("{}\n", contents);
+    match file {
+        Some(f) if append => system().append(f, line.as_bytes()),
+        Some(f) => system().write(f, line.as_bytes()),
+        None => writeln!(stdout(), "{}", contents),
+    }
+}
+
+/// Print or write `$PATH` in a configuration file format.
+pub fn export_path(opts: &ExportOpt) -> io::Result<()> {
+    let path = read_raw_path().unwrap_or_default();
+
+    let target = system().target();
+    let result = path
+        .to_str()
+        .ok_or_else(|| unrepresentable(opts.format, "is not valid UTF-8"))
+        .and_then(|p| match opts.format.is_relative() {
+            true => {
+                let baseline = opts.baseline()?;
+                let baseline = baseline.to_str().ok_or_else(|| {
+                    unrepresentable(opts.format, "has a baseline that is not valid UTF-8")
+                })?;
+                let before = split_like(baseline, &[], target);
+                let after = split_like(p, &before, target);
+                export_relative(p, &PathDiff::new(before, after), opts.format, target)
+            }
+            false => export_line(p, opts.format, target),
+        })
+        .and_then(|contents| {
+            let append = opts.format == ExportFormat::Github;
+            output(&contents, opts.output.as_deref(), append)
+        });
+
+    if let Err(e) = &result {
+        if !opts.quiet {
+            eprintln!("{}", e);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        path::dirs,
+        system::{memory::MemorySystem, with_system},
+    };
+
+    const TRICKY: &str = "/opt/it's/bin:/home/me/$HOME/\"quoted\"/bin:/a\\b@c";
+
+    #[test]
+    fn escape_special_characters() {
+        assert_eq!(
+            export_line(TRICKY, ExportFormat::EnvironmentD, Target::Unix).unwrap(),
+            r#"PATH="/opt/it's/bin:/home/me/\$HOME/\"quoted\"/bin:/a\\b@c""#
+        );
+        assert_eq!(
+            export_line(TRICKY, ExportFormat::Dotenv, Target::Unix).unwrap(),
+            r#"PATH="/opt/it's/bin:/home/me/\$HOME/\"quoted\"/bin:/a\\b@c""#
+        );
+        assert_eq!(
+            export_line(TRICKY, ExportFormat::Pam, Target::Unix).unwrap(),
+            r#"PATH DEFAULT="/opt/it's/bin:/home/me/\$HOME/\"quoted\"/bin:/a\\b\@c""#
+        );
+        assert!(export_line(TRICKY, ExportFormat::EtcEnvironment, Target::Unix).is_err());
+        assert!(export_line("/a\n/b", ExportFormat::Dotenv, Target::Unix).is_err());
+    }
+
+    #[test]
+    fn plain_paths_are_quoted() {
+        let path = "/usr/local/bin:/usr/bin";
+        assert_eq!(
+            export_line(path, ExportFormat::EtcEnvironment, Target::Unix).unwrap(),
+            "PATH=\"/usr/local/bin:/usr/bin\""
+        );
+        assert_eq!(
+            export_line(path, ExportFormat::Dotenv, Target::Unix).unwrap(),
+            "PATH='/usr/local/bin:/usr/bin'"
+        );
+    }
+
+    #[test]
+    fn relative_to_baseline() {
+        let path = "/b:/a:/usr/bin:/my $dir";
+        let diff = PathDiff::new(
+            dirs(&["/usr/bin"]),
+            dirs(&["/b", "/a", "/usr/bin", "/my $dir"]),
+        );
+
+        assert!(export_relative(path, &diff, ExportFormat::Github, Target::Unix).is_err());
+        assert_eq!(
+            export_relative(path, &diff, ExportFormat::Gitlab, Target::Unix).unwrap(),
+            "variables:\n  PATH: \"/b:/a:${PATH}:/my $$dir\""
+        );
+        assert_eq!(
+            export_relative(path, &diff, ExportFormat::Dockerfile, Target::Unix).unwrap(),
+            "ENV PATH=\"/b:/a:${PATH}:/my \\$dir\""
+        );
+
+        let diff = PathDiff::new(dirs(&["/usr/bin"]), dirs(&["/b", "/a", "/usr/bin"]));
+        assert_eq!(
+            export_relative("/b:/a:/usr/bin", &diff, ExportFormat::Github, Target::Unix).unwrap(),
+            "/a\n/b"
+        );
+    }
+
+    #[test]
+    fn windows_target() {
+        let baseline = split_like(r"C:\Windows;C:\Tools", &[], Target::Windows);
+        let after = split_like(r"D:\bin;c:\windows\;C:\Tools", &baseline, Target::Windows);
+        assert_eq!(after[1], PathBuf::from(r"C:\Windows"));
+
+        let diff = PathDiff::new(baseline, after);
+        assert_eq!(
+            export_relative("", &diff, ExportFormat::Gitlab, Target::Windows).unwrap(),
+            r#"variables:
+  PATH: "D:\\bin;${PATH}""#
+        );
+    }
+
+    #[test]
+    fn export_to_file() {
+        let sys = MemorySystem::new().with_path("/a:/b").with_dir("/project");
+        let opts = ExportOpt {
+            format: ExportFormat::Dotenv,
+            baseline: None,
+            output: Some(PathBuf::from("/project/.env")),
+            quiet: true,
+        };
+        with_system(sys.clone(), || export_path(&opts)).unwrap();
+        assert_eq!(sys.contents("/project/.env").unwrap(), "PATH='/a:/b'\n");
+    }
+}
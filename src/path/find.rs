@@ -0,0 +1,159 @@
+//! Find commands provided by the directories in `$PATH`.
+
+use super::list::{inspect_path, path_of};
+use crate::{error::PadError, system::stdout};
+use clap::{crate_authors, Parser, ValueEnum};
+use glob::{MatchOptions, Pattern};
+use regex::RegexBuilder;
+use std::{
+    collections::HashSet,
+    ffi::OsString,
+    io::{self, Write},
+};
+
+/// Checks whether a command name matches a pattern.
+type Matcher = Box<dyn Fn(&str) -> bool>;
+
+/// How a pattern is matched against command names.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum MatchMode {
+    /// Shell-style wildcards, like `python3*`
+    #[default]
+    Glob,
+    /// Regular expression, searched for anywhere in the name
+    Regex,
+    /// All characters of the pattern appear in the name, in order
+    Fuzzy,
+}
+
+#[derive(Debug, Parser)]
+#[clap(
+    about = "Find commands in `$PATH` matching a pattern",
+    author = crate_authors!(),
+)]
+pub struct FindOpt {
+    /// Pattern to match command names against
+    pattern: String,
+
+    /// How to match the pattern
+    #[clap(short, long, value_enum, default_value_t)]
+    mode: MatchMode,
+
+    /// Ignore case when matching
+    #[clap(short = 'i', long)]
+    ignore_case: bool,
+
+    /// Only show the command that would run for each name, not the ones it shadows
+    #[clap(long)]
+    first: bool,
+
+    /// Search the `$PATH` of another running process, instead of this one
+    #[clap(long)]
+    pid: Option<u32>,
+
+    /// Don't print warnings.
+    #[clap(short, long)]
+    quiet: bool,
+}
+
+impl FindOpt {
+    /// Validate options
+    pub fn validate(&self) -> io::Result<()> {
+        if let Err(e) = self.matcher() {
+            if !self.quiet {
+                eprintln!("{}", e);
+            }
+
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// Build a function that checks whether a command name matches the pattern.
+    fn matcher(&self) -> io::Result<Matcher> {
+        let invalid = |e: String| -> io::Error {
+            PadError::InvalidInput(format!("Invalid pattern `{}`. {}", self.pattern, e)).into()
+        };
+
+        match self.mode {
+            MatchMode::Glob => {
+                let pattern = Pattern::new(&self.pattern).map_err(|e| invalid(e.to_string()))?;
+                let options = MatchOptions {
+                    case_sensitive: !self.ignore_case,
+                    ..MatchOptions::new()
+                };
+                Ok(Box::new(move |name| pattern.matches_with(name, options)))
+            }
+            MatchMode::Regex => {
+                let re = RegexBuilder::new(&self.pattern)
+                    .case_insensitive(self.ignore_case)
+                    .build()
+                    .map_err(|e| invalid(e.to_string()))?;
+                Ok(Box::new(move |name| re.is_match(name)))
+            }
+            MatchMode::Fuzzy => {
+                let pattern = self.pattern.clone();
+                let ignore_case = self.ignore_case;
+                Ok(Box::new(move |name| {
+                    fuzzy_match(&pattern, name, ignore_case)
+                }))
+            }
+        }
+    }
+}
+
+/// Check if all characters of `pattern` appear in `name`, in the same order.
+pub fn fuzzy_match(pattern: &str, name: &str, ignore_case: bool) -> bool {
+    let mut name_chars = name.chars();
+    pattern.chars().all(|p| {
+        name_chars.any(|n| match ignore_case {
+            true => n.to_lowercase().eq(p.to_lowercase()),
+            false => n == p,
+        })
+    })
+}
+
+/// Print the commands matching a pattern, in the order they are found in `$PATH`.
+pub fn find_in_path(opts: &FindOpt) -> io::Result<()> {
+    let matches = opts.matcher()?;
+    let entries = match path_of(opts.pid) {
+        Ok(p) => inspect_path(&p, true),
+        Err(e) => {
+            if !opts.quiet {
+                eprintln!("{}", e);
+            }
+            return Err(e);
+        }
+    };
+
+    let mut seen: HashSet<&OsString> = HashSet::new();
+    let mut stdout = stdout();
+    for e in entries.iter().filter(|e| e.duplicate_of.is_none()) {
+        for cmd in &e.commands {
+            if !matches(&cmd.to_string_lossy()) {
+                continue;
+            }
+            // later commands with the same name are shadowed by the first one
+            if !seen.insert(cmd) && opts.first {
+                continue;
+            }
+            writeln!(stdout, "{}", e.raw.join(cmd).display())?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_matches_subsequence() {
+        assert!(fuzzy_match("py3", "python3.11", false));
+        assert!(fuzzy_match("PY", "python", true));
+        assert!(!fuzzy_match("PY", "python", false));
+        assert!(!fuzzy_match("3py", "python3", false));
+    }
+}
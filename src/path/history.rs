@@ -1,71 +1,42 @@
 //! Read and write to the `$PATH` history.
 
-use clap::crate_name;
-use dirs_next::{config_dir, home_dir};
-use rev_lines::RevLines;
+use crate::{config::get_config_dir, error::PadError, system::system};
 use std::{
-    env,
     ffi::{OsStr, OsString},
-    fs::OpenOptions,
-    io::{self, BufReader, Write},
-    path::PathBuf,
+    io,
+    path::{Path, PathBuf},
 };
 
+/// Marks a revision in the history file that had to be escaped.
+///
+/// Revisions that are valid UTF-8 and fit on one line are written as they are,
+/// so that the history file stays readable.
+const ESCAPED_PREFIX: &str = "#escaped:";
+
 /// Check multiple locations for a `$PATH` history file and return the highest
 /// priority one.
 pub fn get_history_filepath() -> PathBuf {
-    // check if $XDG_CONFIG_HOME is set
-    let mut cfg_path = match config_dir() {
-        Some(dir) => dir,
-        // if not set, make it the default $HOME/.config
-        None => {
-            if let Some(mut dir) = home_dir() {
-                dir.push(".config");
-                dir
-            } else {
-                PathBuf::new()
-            }
-        }
-    };
-
-    // get config from within $XDG_CONFIG_HOME
-    cfg_path.push(crate_name!());
-    cfg_path.push(".path_history");
-    cfg_path
+    get_config_dir().join(".path_history")
 }
 
 /// Parse the `$PATH` history.
 ///
-/// For memory constraints, parse the last n lines.
-/// If no limit is specified, load the file carefully.
+/// The revisions are read from the end of the file, with 1 being the most recent.
 pub fn get_nth_last_revision(n: u128) -> io::Result<OsString> {
     // get the history file
+    let sys = system();
     let history_filepath = get_history_filepath();
 
     // error out if the path history does not exist
-    if !history_filepath.exists() {
-        return Err(io::Error::new(
-            io::ErrorKind::NotFound,
-            "History file not found. Nothing to revert to.",
-        ));
+    if !sys.exists(&history_filepath) {
+        return Err(PadError::HistoryEmpty.into());
     }
-    let history_file = OpenOptions::new().read(true).open(history_filepath)?;
-
-    // iterate through the lines in reverse order
-    let rev_lines = RevLines::new(BufReader::new(history_file)).unwrap();
+    let contents = sys.read_to_string(&history_filepath)?;
 
     // error out if the revision is too far back (not enough history in the path history file)
-    let revision_path = match rev_lines.into_iter().nth((n - 1) as usize) {
-        Some(s) => OsString::from(s),
-        None => {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                format!(
-                "History does not contain {} revision(s). Please specify a smaller revision number.",
-                &n
-            ),
-            ))
-        }
+    let revision_path = match contents.lines().rev().nth((n - 1) as usize) {
+        Some(s) => decode_revision(s),
+        None => return Err(PadError::HistoryTooShort(n).into()),
     };
 
     Ok(revision_path)
@@ -74,16 +45,190 @@ pub fn get_nth_last_revision(n: u128) -> io::Result<OsString> {
 /// Append the new `$PATH` to the history file.
 pub fn write_to_history(p: &OsStr) -> io::Result<()> {
     // convert into a writable string
-    let p_str = p.to_str().unwrap();
-    // get the history file
+    let p_str = encode_revision(p);
+    // add it to the end of the history file, creating it and its directory if they don't exist
+    let sys = system();
     let history_filepath = get_history_filepath();
-    // open the file with the appropriate permissions
-    let mut history_file = OpenOptions::new()
-        // create it if it doesn't exist
-        .create(true)
-        // append to the end of the file
-        .append(true)
-        // which file path to write to
-        .open(history_filepath)?;
-    writeln!(history_file, "{}", p_str)
+    history_filepath
+        .parent()
+        .map_or(Ok(()), |dir| sys.create_dir_all(dir))
+        .and_then(|_| sys.append(&history_filepath, format!("{}\n", p_str).as_bytes()))
+        .map_err(|e| write_error(&history_filepath, e))
+}
+
+/// Only keep the most recent `limit` revisions in the history file.
+pub fn trim_history(limit: usize) -> io::Result<()> {
+    let sys = system();
+    let history_filepath = get_history_filepath();
+    if !sys.exists(&history_filepath) {
+        return Ok(());
+    }
+
+    let contents = sys.read_to_string(&history_filepath)?;
+    let lines: Vec<&str> = contents.lines().collect();
+    if lines.len() <= limit {
+        return Ok(());
+    }
+
+    // keep the newest revisions, which are at the end of the file
+    let mut kept = lines[(lines.len() - limit)..].join("\n");
+    if !kept.is_empty() {
+        kept.push('\n');
+    }
+    sys.write(&history_filepath, kept.as_bytes())
+        .map_err(|e| write_error(&history_filepath, e))
+}
+
+/// Say which file couldn't be written, since the error alone doesn't.
+fn write_error(history_filepath: &Path, e: io::Error) -> io::Error {
+    io::Error::new(
+        e.kind(),
+        format!(
+            "Could not write to the history file `{}`. {}",
+            history_filepath.display(),
+            e
+        ),
+    )
+}
+
+/// Convert a `$PATH` into a single line of the history file.
+///
+/// Anything that can't be written as is, like line breaks and bytes that
+/// aren't valid UTF-8, is escaped with a backslash.
+/// An empty `$PATH` is also marked, since blank lines are skipped when reading.
+fn encode_revision(p: &OsStr) -> String {
+    match p.to_str() {
+        Some(s) if !s.is_empty() && !s.contains(['\n', '\r']) && !s.starts_with(ESCAPED_PREFIX) => {
+            String::from(s)
+        }
+        _ => {
+            let mut escaped = String::from(ESCAPED_PREFIX);
+            for chunk in os_str_bytes(p).utf8_chunks() {
+                for c in chunk.valid().chars() {
+                    match c {
+                        '\\' => escaped.push_str("\\\\"),
+                        '\n' => escaped.push_str("\\n"),
+                        '\r' => escaped.push_str("\\r"),
+                        c => escaped.push(c),
+                    }
+                }
+                for b in chunk.invalid() {
+                    escaped.push_str(&format!("\\x{:02x}", b));
+                }
+            }
+            escaped
+        }
+    }
+}
+
+/// Convert a line of the history file back into a `$PATH`.
+fn decode_revision(line: &str) -> OsString {
+    let escaped = match line.strip_prefix(ESCAPED_PREFIX) {
+        Some(e) => e,
+        None => return OsString::from(line),
+    };
+
+    let mut bytes: Vec<u8> = Vec::with_capacity(escaped.len());
+    let mut rest = escaped;
+    while let Some(i) = rest.find('\\') {
+        bytes.extend_from_slice(&rest.as_bytes()[..i]);
+        rest = &rest[(i + 1)..];
+        let (byte, len) = match rest.as_bytes() {
+            [b'\\', ..] => (Some(b'\\'), 1),
+            [b'n', ..] => (Some(b'\n'), 1),
+            [b'r', ..] => (Some(b'\r'), 1),
+            [b'x', ..] => match rest.get(1..3).map(|h| u8::from_str_radix(h, 16)) {
+                Some(Ok(b)) => (Some(b), 3),
+                _ => (None, 0),
+            },
+            _ => (None, 0),
+        };
+        match byte {
+            Some(b) => bytes.push(b),
+            // keep anything that isn't a valid escape sequence as it is
+            None => bytes.push(b'\\'),
+        }
+        rest = &rest[len..];
+    }
+    bytes.extend_from_slice(rest.as_bytes());
+    os_string_from_bytes(bytes)
+}
+
+/// The bytes making up an `OsStr`.
+#[cfg(unix)]
+fn os_str_bytes(s: &OsStr) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    s.as_bytes().to_vec()
+}
+
+/// The bytes making up an `OsStr`.
+///
+/// Only Unix allows arbitrary bytes, so anything else is stored as UTF-8.
+#[cfg(not(unix))]
+fn os_str_bytes(s: &OsStr) -> Vec<u8> {
+    s.to_string_lossy().as_bytes().to_vec()
+}
+
+/// Build an `OsString` from the bytes returned by [`os_str_bytes`].
+#[cfg(unix)]
+fn os_string_from_bytes(bytes: Vec<u8>) -> OsString {
+    use std::os::unix::ffi::OsStringExt;
+    OsString::from_vec(bytes)
+}
+
+/// Build an `OsString` from the bytes returned by [`os_str_bytes`].
+#[cfg(not(unix))]
+fn os_string_from_bytes(bytes: Vec<u8>) -> OsString {
+    OsString::from(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_directory_created() {
+        use crate::system::{memory::MemorySystem, with_system};
+
+        let sys = MemorySystem::new().with_home("/home/me");
+        with_system(sys.clone(), || {
+            write_to_history(OsStr::new("/a")).unwrap();
+            write_to_history(OsStr::new("/a:/b")).unwrap();
+            trim_history(1).unwrap();
+            assert_eq!(get_nth_last_revision(1).unwrap(), "/a:/b");
+            assert!(get_nth_last_revision(2).is_err());
+        });
+        assert_eq!(
+            sys.contents("/home/me/.config/pad-path/.path_history")
+                .unwrap(),
+            "/a:/b\n"
+        );
+    }
+
+    #[test]
+    fn plain_revisions_are_unchanged() {
+        let p = OsStr::new("/usr/bin:C:\\new\\x41");
+        assert_eq!(encode_revision(p), "/usr/bin:C:\\new\\x41");
+        assert_eq!(decode_revision(&encode_revision(p)), p);
+    }
+
+    #[test]
+    fn empty_revision_is_not_blank() {
+        assert_eq!(encode_revision(OsStr::new("")), ESCAPED_PREFIX);
+        assert_eq!(decode_revision(ESCAPED_PREFIX), OsString::new());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn escaped_revisions_round_trip() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let p = OsStr::from_bytes(b"/a\xff\\b:/c\nd:/e\\x41");
+        let line = encode_revision(p);
+        assert_eq!(line, "#escaped:/a\\xff\\\\b:/c\\nd:/e\\\\x41");
+        assert_eq!(decode_revision(&line), p);
+
+        let p = OsStr::new("#escaped:/a");
+        assert_eq!(decode_revision(&encode_revision(p)), p);
+    }
 }
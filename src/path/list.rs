@@ -0,0 +1,520 @@
+//! List the directories in `$PATH`.
+
+use super::{
+    clean::{clean_entry, is_relative_entry},
+    executables::list_executables,
+    read::{read_process_path, read_raw_path, split_raw_path},
+};
+use crate::{
+    config::{config, ColorChoice},
+    system::{stdout, system},
+};
+use clap::{crate_authors, Parser, ValueEnum};
+use serde::{Serialize, Serializer};
+use std::{
+    collections::HashSet,
+    ffi::{OsStr, OsString},
+    io::{self, IsTerminal, Write},
+    path::{Path, PathBuf},
+};
+
+/// Output formats for listing `$PATH`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ListFormat {
+    /// One directory per line
+    #[default]
+    Plain,
+    /// Tab-separated table with a header row
+    Tsv,
+    /// JSON array with details about each directory
+    Json,
+}
+
+#[derive(Debug, Default, Parser)]
+#[clap(
+    about = "List the directories in `$PATH`",
+    author = crate_authors!(),
+    visible_alias = "echo",
+)]
+pub struct LsOpt {
+    /// Output format
+    #[clap(short, long, value_enum, default_value_t)]
+    format: ListFormat,
+
+    /// Separate directories with NUL characters instead of newlines (`plain` and `tsv` only)
+    #[clap(short = '0', long)]
+    null: bool,
+
+    /// Show the position of each directory and highlight potential problems
+    #[clap(short, long, conflicts_with_all = &["format", "null"])]
+    annotate: bool,
+
+    /// Like `--annotate`, but also show permissions, owners, and the number of executables
+    #[clap(short, long, conflicts_with_all = &["format", "null"])]
+    long: bool,
+
+    /// Also list the executables in each directory (not available for `tsv`)
+    #[clap(short = 'x', long, conflicts_with = "null")]
+    executables: bool,
+
+    /// When to colourize annotations
+    #[clap(long, value_enum)]
+    color: Option<ColorChoice>,
+
+    /// List the `$PATH` of another running process, instead of this one
+    #[clap(long)]
+    pid: Option<u32>,
+}
+
+impl LsOpt {
+    /// Validate options
+    pub fn validate(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Get the `$PATH` of another process if a PID is given, or of this one otherwise.
+pub fn path_of(pid: Option<u32>) -> io::Result<OsString> {
+    match pid {
+        Some(p) => read_process_path(p),
+        None => Ok(read_raw_path().unwrap_or_default()),
+    }
+}
+
+/// Details about a single directory in `$PATH`.
+#[derive(Debug, Serialize)]
+pub struct PathEntry {
+    /// Position in `$PATH`, starting from 0
+    pub index: usize,
+    /// The directory exactly as it appears in `$PATH`
+    #[serde(serialize_with = "serialize_lossy")]
+    pub raw: PathBuf,
+    /// The directory after cleaning its name
+    #[serde(serialize_with = "serialize_lossy")]
+    pub normalized: PathBuf,
+    /// Whether the directory exists
+    pub exists: bool,
+    /// Whether this is a directory (and not a file)
+    pub is_dir: bool,
+    /// Whether this is a symbolic link
+    pub is_symlink: bool,
+    /// Whether this is empty or relative, and so depends on the current directory
+    pub is_relative: bool,
+    /// Where the directory points to, after resolving all symlinks
+    #[serde(serialize_with = "serialize_lossy_opt")]
+    pub canonical: Option<PathBuf>,
+    /// Index of an earlier entry that refers to the same directory
+    pub duplicate_of: Option<usize>,
+    /// Number of executables directly within the directory, if they were listed
+    pub executables: usize,
+    /// Indices of later entries with commands that are hidden by this one, if
+    /// executables were listed
+    pub shadows: Vec<usize>,
+    /// Names of the executables directly within the directory, if they were listed
+    #[serde(skip)]
+    pub commands: Vec<OsString>,
+}
+
+/// Serialize a path as a string, replacing anything that isn't valid UTF-8.
+///
+/// JSON can only contain Unicode, so this is the closest representation.
+fn serialize_lossy<S: Serializer>(p: &Path, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&p.to_string_lossy())
+}
+
+/// Serialize an optional path as a string, replacing anything that isn't valid UTF-8.
+fn serialize_lossy_opt<S: Serializer>(p: &Option<PathBuf>, s: S) -> Result<S::Ok, S::Error> {
+    match p {
+        Some(p) => serialize_lossy(p, s),
+        None => s.serialize_none(),
+    }
+}
+
+/// A directory in `$PATH` along with the names of its executables.
+#[derive(Serialize)]
+struct EntryWithCommands<'a> {
+    #[serde(flatten)]
+    entry: &'a PathEntry,
+    commands: Vec<String>,
+}
+
+/// Inspect every directory in a `$PATH`-like string.
+///
+/// Listing the executables in every directory is slow for a long `$PATH`, so
+/// it is only done with `commands`. Otherwise, the executables and the commands
+/// each directory shadows are left empty.
+pub fn inspect_path(raw_path: &OsStr, commands: bool) -> Vec<PathEntry> {
+    let mut entries: Vec<PathEntry> = vec![];
+    let sys = system();
+
+    for (index, raw) in split_raw_path(raw_path).into_iter().enumerate() {
+        let normalized = clean_entry(&raw);
+        // an empty entry is the current directory
+        let dir = match raw.as_os_str().is_empty() {
+            true => Path::new("."),
+            false => raw.as_path(),
+        };
+        let canonical = sys.canonicalize(dir).ok();
+        let duplicate_of = entries
+            .iter()
+            .find(|e| {
+                e.normalized == normalized || (canonical.is_some() && e.canonical == canonical)
+            })
+            .map(|e| e.index);
+        let commands: Vec<OsString> = match commands {
+            true => list_executables(dir)
+                .iter()
+                .filter_map(|p| p.file_name().map(OsString::from))
+                .collect(),
+            false => vec![],
+        };
+
+        entries.push(PathEntry {
+            index,
+            exists: sys.exists(dir),
+            is_dir: sys.is_dir(dir),
+            is_symlink: sys.is_symlink(dir),
+            is_relative: is_relative_entry(&raw),
+            executables: commands.len(),
+            shadows: vec![],
+            commands,
+            raw,
+            normalized,
+            canonical,
+            duplicate_of,
+        });
+    }
+
+    // a command in an earlier directory hides any command with the same name
+    // in a later directory
+    for i in 0..entries.len() {
+        if entries[i].duplicate_of.is_some() {
+            continue;
+        }
+        let cmds: HashSet<&OsString> = entries[i].commands.iter().collect();
+        let shadows: Vec<usize> = entries[(i + 1)..]
+            .iter()
+            .filter(|later| later.duplicate_of.is_none())
+            .filter(|later| later.commands.iter().any(|c| cmds.contains(c)))
+            .map(|later| later.index)
+            .collect();
+        entries[i].shadows = shadows;
+    }
+
+    entries
+}
+
+/// Print the directories in the current `$PATH`.
+pub fn list_path(opts: &LsOpt) -> io::Result<()> {
+    let raw_path = match path_of(opts.pid) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("{}", e);
+            return Err(e);
+        }
+    };
+    // plain names are all that's needed without any details
+    let details =
+        opts.format != ListFormat::Plain || opts.annotate || opts.long || opts.executables;
+    let entries = inspect_path(&raw_path, details);
+    let terminator: &[u8] = match opts.null {
+        true => b"\0",
+        false => b"\n",
+    };
+
+    let mut stdout = stdout();
+    if opts.annotate || opts.long {
+        let color = opts
+            .color
+            .unwrap_or(config().color)
+            .enabled(io::stdout().is_terminal());
+        for e in &entries {
+            writeln!(stdout, "{}", annotate_entry(e, opts.long, color))?;
+            if opts.executables {
+                write_commands(&mut stdout, e)?;
+            }
+        }
+        return Ok(());
+    }
+
+    match opts.format {
+        ListFormat::Plain => {
+            for e in &entries {
+                stdout.write_all(e.normalized.as_os_str().as_encoded_bytes())?;
+                stdout.write_all(terminator)?;
+                if opts.executables {
+                    write_commands(&mut stdout, e)?;
+                }
+            }
+        }
+        ListFormat::Tsv => {
+            stdout.write_all(
+                b"index\traw\tnormalized\texists\tis_dir\tis_symlink\tcanonical\tduplicate_of\texecutables\tis_relative",
+            )?;
+            stdout.write_all(terminator)?;
+            for e in &entries {
+                let row = [
+                    e.index.to_string(),
+                    tsv_field(e.raw.as_os_str()),
+                    tsv_field(e.normalized.as_os_str()),
+                    e.exists.to_string(),
+                    e.is_dir.to_string(),
+                    e.is_symlink.to_string(),
+                    e.canonical
+                        .as_ref()
+                        .map(|c| tsv_field(c.as_os_str()))
+                        .unwrap_or_default(),
+                    e.duplicate_of.map(|i| i.to_string()).unwrap_or_default(),
+                    e.executables.to_string(),
+                    e.is_relative.to_string(),
+                ];
+                stdout.write_all(row.join("\t").as_bytes())?;
+                stdout.write_all(terminator)?;
+            }
+        }
+        ListFormat::Json if opts.executables => {
+            let entries: Vec<EntryWithCommands> = entries
+                .iter()
+                .map(|e| EntryWithCommands {
+                    entry: e,
+                    commands: e
+                        .commands
+                        .iter()
+                        .map(|c| c.to_string_lossy().to_string())
+                        .collect(),
+                })
+                .collect();
+            serde_json::to_writer_pretty(&mut stdout, &entries)?;
+            writeln!(stdout)?;
+        }
+        ListFormat::Json => {
+            serde_json::to_writer_pretty(&mut stdout, &entries)?;
+            writeln!(stdout)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Print the names of the executables in a directory, indented beneath it.
+fn write_commands<W: Write>(w: &mut W, e: &PathEntry) -> io::Result<()> {
+    for cmd in &e.commands {
+        w.write_all(b"  ")?;
+        w.write_all(cmd.as_encoded_bytes())?;
+        w.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// ANSI colours used to highlight annotations.
+const BOLD_RED: &str = "1;31";
+const YELLOW: &str = "33";
+const CYAN: &str = "36";
+const MAGENTA: &str = "35";
+const DIM: &str = "2";
+
+/// Wrap text in an ANSI colour code, if colours are enabled.
+fn paint(s: &str, code: &str, color: bool) -> String {
+    match color {
+        true => format!("\x1b[{}m{}\x1b[0m", code, s),
+        false => s.to_string(),
+    }
+}
+
+/// Format a single line of the annotated listing.
+fn annotate_entry(e: &PathEntry, long: bool, color: bool) -> String {
+    let mut line = paint(&format!("{:>3}", e.index), DIM, color);
+    line.push_str("  ");
+
+    if long {
+        // empty and relative entries are only found from the current directory
+        let dir = e.canonical.as_deref().unwrap_or(&e.normalized);
+        line.push_str(&format!(
+            "{} {:<10} {:>5}  ",
+            permissions(dir),
+            owner(dir),
+            e.executables
+        ));
+    }
+
+    line.push_str(&e.raw.to_string_lossy());
+    if e.is_symlink {
+        let target = e
+            .canonical
+            .as_ref()
+            .map(|c| c.to_string_lossy().to_string())
+            .unwrap_or_else(|| String::from("?"));
+        line.push_str(&paint(&format!(" -> {}", target), CYAN, color));
+    }
+
+    let mut notes: Vec<String> = vec![];
+    if e.is_relative {
+        notes.push(paint("relative", BOLD_RED, color));
+    }
+    if !e.exists {
+        notes.push(paint("missing", BOLD_RED, color));
+    } else if !e.is_dir {
+        notes.push(paint("not a directory", BOLD_RED, color));
+    }
+    if let Some(i) = e.duplicate_of {
+        notes.push(paint(&format!("duplicate of {}", i), YELLOW, color));
+    }
+    if !e.shadows.is_empty() {
+        let shadowed: Vec<String> = e.shadows.iter().map(|i| i.to_string()).collect();
+        notes.push(paint(
+            &format!("shadows {}", shadowed.join(", ")),
+            MAGENTA,
+            color,
+        ));
+    }
+    if !notes.is_empty() {
+        line.push_str(&format!("  [{}]", notes.join("; ")));
+    }
+
+    line
+}
+
+/// Format the permissions of a directory like `ls -l`, or as readonly or
+/// writable where there are no Unix permission bits.
+fn permissions(p: &Path) -> String {
+    let md = match system().metadata(p) {
+        Ok(md) => md,
+        Err(_) if cfg!(unix) => return String::from("----------"),
+        Err(_) => return String::from("-"),
+    };
+    let mode = match md.mode {
+        Some(mode) => mode,
+        None if md.readonly => return String::from("readonly"),
+        None => return String::from("writable"),
+    };
+    let mut s = String::from(if md.is_dir { "d" } else { "-" });
+    for shift in [6, 3, 0] {
+        let bits = (mode >> shift) & 0o7;
+        s.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        s.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        s.push(if bits & 0o1 != 0 { 'x' } else { '-' });
+    }
+    s
+}
+
+/// Find the name of the user that owns a directory.
+fn owner(p: &Path) -> String {
+    let uid = match system().metadata(p).map(|md| md.uid) {
+        Ok(Some(uid)) => uid,
+        _ => return String::from("-"),
+    };
+
+    // look up the user name, falling back to the numeric ID
+    system()
+        .read_to_string(Path::new("/etc/passwd"))
+        .ok()
+        .and_then(|passwd| {
+            passwd.lines().find_map(|l| {
+                let fields: Vec<&str> = l.split(':').collect();
+                match fields.get(2).map(|id| id.parse::<u32>()) {
+                    Some(Ok(id)) if id == uid => Some(fields[0].to_string()),
+                    _ => None,
+                }
+            })
+        })
+        .unwrap_or_else(|| uid.to_string())
+}
+
+/// Escape characters that would break the structure of a TSV file.
+fn tsv_field(s: &OsStr) -> String {
+    s.to_string_lossy()
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_path_has_no_entries() {
+        assert!(inspect_path(&OsString::new(), true).is_empty());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn non_utf8_serialized_lossily() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let entries = inspect_path(OsStr::from_bytes(b"/pad-path/\xff"), false);
+        let json = serde_json::to_value(&entries).unwrap();
+        assert_eq!(json[0]["raw"], "/pad-path/\u{fffd}");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn duplicates_refer_to_first_entry() {
+        let entries = inspect_path(
+            &OsString::from("/pad-path/a:/pad-path/b:/pad-path/a/"),
+            false,
+        );
+        let duplicates: Vec<Option<usize>> = entries.iter().map(|e| e.duplicate_of).collect();
+        assert_eq!(duplicates, vec![None, None, Some(0)]);
+        assert!(entries.iter().all(|e| !e.exists));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn annotations_without_color() {
+        let entries = inspect_path(&OsString::from("/pad-path/a:/pad-path/a"), true);
+        assert_eq!(
+            annotate_entry(&entries[1], false, false),
+            "  1  /pad-path/a  [missing; duplicate of 0]"
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn executables_only_listed_when_asked() {
+        use crate::system::{memory::MemorySystem, with_system};
+
+        let sys = MemorySystem::new()
+            .with_file("/a/cmd", true)
+            .with_file("/b/cmd", true);
+        with_system(sys, || {
+            let entries = inspect_path(OsStr::new("/a:/b"), true);
+            assert_eq!(entries[0].executables, 1);
+            assert_eq!(entries[0].shadows, vec![1]);
+
+            let entries = inspect_path(OsStr::new("/a:/b"), false);
+            assert_eq!(entries[0].executables, 0);
+            assert!(entries[0].shadows.is_empty() && entries[0].exists);
+        });
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn long_listing_details() {
+        use crate::system::{memory::MemorySystem, with_system};
+
+        let sys = MemorySystem::new().with_file("/a/cmd", true).with_contents(
+            "/etc/passwd",
+            "root:x:0:0::/root:/bin/sh\nme:x:1000:1000::/home/me:/bin/sh",
+        );
+        with_system(sys.clone(), || {
+            assert_eq!(permissions(Path::new("/a")), "drwxr-xr-x");
+            assert_eq!(owner(Path::new("/a")), "me");
+            assert_eq!(owner(Path::new("/missing")), "-");
+        });
+
+        // empty and relative entries are looked up from the current directory
+        with_system(sys.with_cwd("/a"), || {
+            let entries = inspect_path(OsStr::new(":."), true);
+            for e in &entries {
+                assert!(annotate_entry(e, true, false).contains("drwxr-xr-x me             1  "));
+            }
+        });
+    }
+
+    #[test]
+    fn tsv_fields_are_escaped() {
+        assert_eq!(tsv_field(OsStr::new("a\tb\nc")), "a\\tb\\nc");
+    }
+}
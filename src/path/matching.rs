@@ -0,0 +1,151 @@
+//! Match directories given as arguments against the directories in `$PATH`.
+
+use super::{clean::clean_dir_name, find::fuzzy_match, read::foreign_target};
+use crate::{error::PadError, system::system};
+use std::{
+    io,
+    path::{Path, PathBuf},
+    slice,
+};
+
+/// Find the position of a directory given as an argument in a list of directories.
+///
+/// The argument matches a directory that is spelled the same way, or that is
+/// the same once the argument is cleaned like the directories given to `pad add`.
+/// Otherwise, it matches the first absolute directory that resolves to the
+/// same place, so that different spellings through symlinks also match.
+/// For a `$PATH` written for another platform, names that the platform
+/// considers the same match instead, like `C:\Tools` and `c:/tools` on Windows.
+pub fn find_dir(dirs: &[PathBuf], dir: &Path) -> Option<usize> {
+    if let Some(i) = dirs.iter().position(|d| d == dir) {
+        return Some(i);
+    }
+
+    let cleaned = clean_dir_name(dir);
+    if let Some(i) = dirs.iter().position(|d| *d == cleaned) {
+        return Some(i);
+    }
+
+    if let Some(t) = foreign_target() {
+        let name = cleaned.to_string_lossy();
+        return dirs
+            .iter()
+            .position(|d| t.same_dir(&d.to_string_lossy(), &name));
+    }
+
+    let sys = system();
+    let canonical = sys.canonicalize(&cleaned).ok()?;
+    dirs.iter()
+        .position(|d| d.is_absolute() && sys.canonicalize(d).is_ok_and(|c| c == canonical))
+}
+
+/// Find the position of a directory given as an argument, or by part of its name.
+///
+/// Without `fuzzy`, this is the same as [`find_dir`].
+/// Otherwise, a directory that isn't found is compared with the directories'
+/// last folder names, then with their full names, and then as a fuzzy pattern
+/// whose characters appear in order. The first of these that matches anything
+/// must match exactly one directory, counting each place that `$PATH` lists
+/// the same directory only once.
+pub fn resolve_dir(dirs: &[PathBuf], dir: &Path, fuzzy: bool) -> io::Result<usize> {
+    if let Some(i) = find_dir(dirs, dir) {
+        return Ok(i);
+    }
+    if !fuzzy {
+        return Err(PadError::NotFound(dir.to_path_buf()).into());
+    }
+
+    let pattern = dir.to_string_lossy();
+    let by_basename = |d: &Path| {
+        d.file_name()
+            .is_some_and(|n| n.to_string_lossy() == pattern)
+    };
+    let by_substring = |d: &Path| d.to_string_lossy().contains(pattern.as_ref());
+    let by_fuzzy = |d: &Path| fuzzy_match(&pattern, &d.to_string_lossy(), true);
+    let matchers: [&dyn Fn(&Path) -> bool; 3] = [&by_basename, &by_substring, &by_fuzzy];
+
+    for matches in matchers {
+        // keep the first position of each directory, like `find_dir` does
+        let mut found: Vec<usize> = vec![];
+        for i in (0..dirs.len()).filter(|i| matches(&dirs[*i])) {
+            if !found
+                .iter()
+                .any(|j| find_dir(slice::from_ref(&dirs[*j]), &dirs[i]).is_some())
+            {
+                found.push(i);
+            }
+        }
+
+        match found[..] {
+            [] => continue,
+            [i] => return Ok(i),
+            _ => {
+                let candidates = found.iter().map(|i| dirs[*i].clone()).collect();
+                return Err(PadError::Ambiguous(dir.to_path_buf(), candidates).into());
+            }
+        }
+    }
+
+    Err(PadError::NotFound(dir.to_path_buf()).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::dirs;
+    #[cfg(unix)]
+    use crate::system::{memory::MemorySystem, with_system};
+
+    #[test]
+    #[cfg(unix)]
+    fn spellings_of_the_same_dir_match() {
+        let dirs = dirs(&["/usr/bin", "/home/me/bin", "bin"]);
+        let sys = MemorySystem::new()
+            .with_dir("/usr/bin")
+            .with_dir("/home/me/bin")
+            .with_symlink("/home/me/tools", "bin")
+            .with_cwd("/home/me");
+
+        with_system(sys, || {
+            assert_eq!(find_dir(&dirs, Path::new("bin")), Some(2));
+            assert_eq!(find_dir(&dirs, Path::new("./bin/")), Some(1));
+            assert_eq!(find_dir(&dirs, Path::new("/home/me/bin/")), Some(1));
+            assert_eq!(find_dir(&dirs, Path::new("tools")), Some(1));
+            assert_eq!(find_dir(&dirs, Path::new("../../usr//bin")), Some(0));
+            assert_eq!(find_dir(&dirs, Path::new("/opt/bin")), None);
+        });
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn fuzzy_matches_must_be_unique() {
+        let dirs = dirs(&["/home/me/.cargo/bin", "/home/me/bin", "/usr/local/bin"]);
+        let code = |dir: &str| {
+            PadError::from(resolve_dir(&dirs, Path::new(dir), true).unwrap_err()).exit_code()
+        };
+
+        with_system(MemorySystem::new(), || {
+            assert_eq!(resolve_dir(&dirs, Path::new("cargo"), true).unwrap(), 0);
+            assert_eq!(resolve_dir(&dirs, Path::new("local"), true).unwrap(), 2);
+            assert_eq!(resolve_dir(&dirs, Path::new("ulb"), true).unwrap(), 2);
+            assert!(resolve_dir(&dirs, Path::new("cargo"), false).is_err());
+            assert_eq!(code("bin"), 10);
+            assert_eq!(code("python"), 3);
+        });
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn fuzzy_duplicates_are_one_candidate() {
+        let dirs = dirs(&["/t/a", "/t/b", "/t/a/", "/x/ab"]);
+        let sys = MemorySystem::new()
+            .with_dir("/t/a")
+            .with_dir("/t/b")
+            .with_symlink("/x/ab", "/t/a");
+
+        with_system(sys, || {
+            assert_eq!(resolve_dir(&dirs, Path::new("a"), true).unwrap(), 0);
+            assert_eq!(resolve_dir(&dirs, Path::new("/a"), true).unwrap(), 0);
+        });
+    }
+}
@@ -1,10 +1,32 @@
 //! Read, write, and process the current `$PATH`.
 
 pub mod add;
+pub mod apply;
+pub mod batch;
 pub mod clean;
+pub mod convert;
+pub mod diff;
+pub mod edit;
+pub mod exec;
+pub mod executables;
+pub mod export;
+pub mod find;
 pub mod history;
+pub mod list;
+pub mod matching;
+pub mod ops;
 pub mod priority;
 pub mod read;
 pub mod remove;
 pub mod revert;
+pub mod target;
 pub mod write;
+
+#[cfg(test)]
+use std::path::PathBuf;
+
+/// Build a list of directories from their names.
+#[cfg(test)]
+pub fn dirs(names: &[&str]) -> Vec<PathBuf> {
+    names.iter().map(PathBuf::from).collect()
+}
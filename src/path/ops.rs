@@ -0,0 +1,140 @@
+//! Modify `$PATH` with a series of operations given on the command line.
+
+use super::{
+    add::{add_dirs, check_dirs_exist},
+    clean::clean_dirs_names,
+    matching::find_dir,
+    priority::move_dir,
+    remove::remove_dir,
+};
+use crate::error::PadError;
+use clap::Args;
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Operations to apply to `$PATH` before using it.
+///
+/// Operations are applied in the following order: removals, additions, and
+/// then changes in priority.
+#[derive(Debug, Args)]
+pub struct OpsOpt {
+    /// Add a directory to the beginning of `$PATH`
+    #[clap(short, long, value_name = "DIR")]
+    pub prepend: Vec<PathBuf>,
+
+    /// Add a directory to the end of `$PATH`
+    #[clap(short, long, value_name = "DIR", visible_alias = "add")]
+    pub append: Vec<PathBuf>,
+
+    /// Remove a directory from `$PATH`
+    #[clap(long, value_name = "DIR")]
+    pub rm: Vec<PathBuf>,
+
+    /// Increase the priority of a directory by one spot
+    #[clap(long, value_name = "DIR")]
+    pub up: Vec<PathBuf>,
+
+    /// Decrease the priority of a directory by one spot
+    #[clap(long, value_name = "DIR")]
+    pub dn: Vec<PathBuf>,
+
+    /// Forcefully add directories that don't necessarily exist.
+    #[clap(short, long)]
+    pub force: bool,
+}
+
+impl OpsOpt {
+    /// Validate options
+    pub fn validate(&self) -> io::Result<()> {
+        if self.force {
+            return Ok(());
+        }
+
+        let added: Vec<PathBuf> = self
+            .prepend
+            .iter()
+            .chain(self.append.iter())
+            .cloned()
+            .collect();
+        check_dirs_exist(&added)
+    }
+
+    /// Apply all operations to a list of directories.
+    pub fn apply(&self, current: Vec<PathBuf>) -> io::Result<Vec<PathBuf>> {
+        let mut vpath = current;
+
+        for d in &self.rm {
+            if find_dir(&vpath, d).is_none() {
+                return Err(not_found(d));
+            }
+            vpath = remove_dir(vpath, d);
+        }
+
+        vpath = add_dirs(vpath, clean_dirs_names(&self.prepend), true);
+        vpath = add_dirs(vpath, clean_dirs_names(&self.append), false);
+
+        let moves = self
+            .up
+            .iter()
+            .map(|d| (d, -1))
+            .chain(self.dn.iter().map(|d| (d, 1)));
+        for (d, jump) in moves {
+            match find_dir(&vpath, d) {
+                Some(i) => vpath = move_dir(vpath, i, jump),
+                None => return Err(not_found(d)),
+            }
+        }
+
+        Ok(vpath)
+    }
+}
+
+/// Error for a directory that isn't in `$PATH`.
+pub fn not_found(d: &Path) -> io::Error {
+    PadError::NotFound(d.to_path_buf()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::dirs;
+
+    #[test]
+    fn move_dir_within_bounds() {
+        let current = dirs(&["/a", "/b", "/c"]);
+        assert_eq!(move_dir(current.clone(), 2, -1), dirs(&["/a", "/c", "/b"]));
+        assert_eq!(move_dir(current.clone(), 1, -5), dirs(&["/b", "/a", "/c"]));
+        assert_eq!(move_dir(current, 0, 5), dirs(&["/b", "/c", "/a"]));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn ops_applied_in_order() {
+        let ops = OpsOpt {
+            prepend: dirs(&["/new"]),
+            append: vec![],
+            rm: dirs(&["/b"]),
+            up: dirs(&["/c"]),
+            dn: vec![],
+            force: true,
+        };
+        let observed = ops.apply(dirs(&["/a", "/b", "/c"])).unwrap();
+        assert_eq!(observed, dirs(&["/new", "/c", "/a"]));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn rm_missing_dir_fails() {
+        let ops = OpsOpt {
+            prepend: vec![],
+            append: vec![],
+            rm: dirs(&["/pad-path/does/not/exist"]),
+            up: vec![],
+            dn: vec![],
+            force: false,
+        };
+        assert!(ops.apply(dirs(&["/a"])).is_err());
+    }
+}
@@ -1,11 +1,13 @@
 //! Change the priority of a directory in `$PATH`.
 
 use super::{
+    matching::resolve_dir,
     read::{combine_path_like, read_path},
-    write::replace_path,
+    write::{replace_path, WriteOpt},
 };
+use crate::config::config;
 use clap::Parser;
-use std::{cmp::min, io, path::PathBuf};
+use std::{io, path::PathBuf};
 
 #[derive(Debug, Parser)]
 pub struct MvOpt {
@@ -17,17 +19,12 @@ pub struct MvOpt {
     #[clap(default_value = "1")]
     jump: usize,
 
-    /// Don't print warnings when modifying `$PATH`.
-    #[clap(short, long)]
-    quiet: bool,
+    /// Match `DIR` by part of its name if it isn't in `$PATH`
+    #[clap(long)]
+    fuzzy: bool,
 
-    /// Add current `$PATH` to the history
-    #[clap(short = 'H', long)]
-    history: bool,
-
-    /// Don't do anything, just preview what this command would do
-    #[clap(short = 'n', long = "dry-run")]
-    dry_run: bool,
+    #[clap(flatten)]
+    pub write: WriteOpt,
 }
 
 impl MvOpt {
@@ -45,100 +42,19 @@ impl MvOpt {
 /// in priority (a larger index value).
 fn change_priority(opts: &MvOpt, direction_factor: i8) -> io::Result<()> {
     let current_path = read_path();
-    let idx = current_path.iter().position(|x| *x == opts.dir);
-    // if the directory is found within `$PATH`
-    if let Some(i) = idx {
-        // calculate the new position for `dir`, and ensure that it is within the appropriate bounds
-        let i_signed = i as i8;
-
-        let signed_jump = direction_factor * (opts.jump as i8);
-        let signed_new_idx = i_signed + signed_jump;
-        let new_idx: usize;
-        if signed_new_idx < 0 {
-            new_idx = 0;
-        } else {
-            new_idx = min((signed_new_idx) as usize, current_path.len() - 1);
-        }
-
-        // if moving to a higher priority
-        let vpath: Vec<PathBuf> = match signed_jump.cmp(&0) {
-            std::cmp::Ordering::Less => {
-                // get the first few elements of PATH
-                let mut _vpath: Vec<PathBuf> = (0..new_idx)
-                    .into_iter()
-                    .map(|j| current_path[j].clone())
-                    .collect();
-                // move `dir` into the next position
-                _vpath.push(opts.dir.clone());
-                // add remaining elements
-                _vpath.append(
-                    &mut (new_idx..i)
-                        .into_iter()
-                        .map(|j| current_path[j].clone())
-                        .collect(),
-                );
-                _vpath.append(
-                    &mut ((i + 1)..current_path.len())
-                        .into_iter()
-                        .map(|j| current_path[j].clone())
-                        .collect(),
-                );
-
-                _vpath
-            }
-            std::cmp::Ordering::Equal => current_path,
-            std::cmp::Ordering::Greater => {
-                // get the first few elements of PATH
-                let mut _vpath: Vec<PathBuf> = (0..i)
-                    .into_iter()
-                    .map(|j| current_path[j].clone())
-                    .collect();
-                _vpath.append(
-                    &mut ((i + 1)..(new_idx + 1))
-                        .into_iter()
-                        .map(|j| current_path[j].clone())
-                        .collect(),
-                );
-                // move `dir` into the next position
-                _vpath.push(opts.dir.clone());
-                // add remaining elements
-                _vpath.append(
-                    &mut ((new_idx + 1)..current_path.len())
-                        .into_iter()
-                        .map(|j| current_path[j].clone())
-                        .collect(),
-                );
-
-                _vpath
-            }
-        };
-
-        let newpath = combine_path_like(vpath)?;
-        match replace_path(newpath, opts.dry_run, opts.history, opts.quiet) {
-            Ok(()) => Ok(()),
-            Err(e) => {
-                if !opts.quiet {
-                    eprintln!("{}", e);
-                }
-
-                Err(e)
-            }
-        }
-    } else {
-        let err = io::Error::new(
-            io::ErrorKind::NotFound,
-            format!(
-                "Directory `{}` not found in `$PATH`. No changes made.",
-                opts.dir.display()
-            ),
-        );
-
-        if !opts.quiet {
-            eprintln!("{}", err);
+    let result =
+        resolve_dir(&current_path, &opts.dir, opts.fuzzy || config().fuzzy).and_then(|i| {
+            let signed_jump = direction_factor as isize * opts.jump as isize;
+            let vpath = move_dir(current_path, i, signed_jump);
+            replace_path(combine_path_like(vpath)?, &opts.write)
+        });
+
+    if let Err(e) = &result {
+        if !opts.write.quiet {
+            eprintln!("{}", e);
         }
-
-        Err(err)
     }
+    result
 }
 
 /// Increase the priority of a directory in `$PATH`.
@@ -150,3 +66,15 @@ pub fn increase_priority(opts: &MvOpt) -> io::Result<()> {
 pub fn decrease_priority(opts: &MvOpt) -> io::Result<()> {
     change_priority(opts, 1)
 }
+
+/// Move the directory at index `i` by `jump` spots, within the bounds of `dirs`.
+///
+/// A negative `jump` moves the directory earlier (higher priority), and a
+/// positive `jump` moves it later (lower priority).
+pub fn move_dir(mut dirs: Vec<PathBuf>, i: usize, jump: isize) -> Vec<PathBuf> {
+    let last = dirs.len() as isize - 1;
+    let new_idx = (i as isize).saturating_add(jump).clamp(0, last) as usize;
+    let dir = dirs.remove(i);
+    dirs.insert(new_idx, dir);
+    dirs
+}
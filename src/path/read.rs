@@ -1,24 +1,86 @@
 //! Read the current `$PATH`.
 
 use std::{
-    env::{join_paths, split_paths, var_os},
+    borrow::Cow,
+    env::{join_paths, split_paths},
     ffi::{OsStr, OsString},
     io,
     path::PathBuf,
 };
 
-use crate::path::clean::clean_dir_name;
+use crate::{
+    error::PadError,
+    path::{clean::clean_entry, target::Target},
+    system::system,
+};
 
 /// Get the value for the `$PATH` environment variable.
 pub fn read_raw_path() -> Option<OsString> {
-    var_os("PATH")
+    system().var_os("PATH")
+}
+
+/// The platform `$PATH` is written for, if it isn't the one `pad` is running on.
+///
+/// The directories of another platform can't be looked at, so they are only
+/// compared by name.
+pub fn foreign_target() -> Option<Target> {
+    let target = system().target();
+    (target != Target::host()).then_some(target)
+}
+
+/// Get the value of `$PATH` for another running process.
+#[cfg(target_os = "linux")]
+pub fn read_process_path(pid: u32) -> io::Result<OsString> {
+    use std::{os::unix::ffi::OsStrExt, path::Path};
+
+    let environ = system()
+        .read(Path::new(&format!("/proc/{}/environ", pid)))
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => PadError::ProcessNotFound(pid).into(),
+            kind => io::Error::new(
+                kind,
+                format!("Could not read the environment of process {}. {}", pid, e),
+            ),
+        })?;
+
+    // variables are separated by NUL characters, as `NAME=value`
+    // an unset `$PATH` has no directories, the same as for this process
+    Ok(environ
+        .split(|b| *b == 0)
+        .find_map(|var| var.strip_prefix(b"PATH="))
+        .map(|p| OsStr::from_bytes(p).to_os_string())
+        .unwrap_or_default())
+}
+
+/// Get the value of `$PATH` for another running process.
+#[cfg(not(target_os = "linux"))]
+pub fn read_process_path(pid: u32) -> io::Result<OsString> {
+    Err(PadError::Unsupported(format!(
+        "Reading the `$PATH` of process {} is only supported on Linux.",
+        pid
+    ))
+    .into())
 }
 
 /// Get the value for the `$PATH` environment variable, split across a vector.
+///
+/// An unset `$PATH` is treated the same as an empty one, with no directories.
 pub fn read_path() -> Vec<PathBuf> {
-    match read_raw_path() {
-        Some(path_str) => split_path_like(&path_str),
-        None => vec![PathBuf::from("")],
+    split_path_like(&read_raw_path().unwrap_or_default())
+}
+
+/// Split an `OsString` formatted like a `$PATH` into a `Vec`, without cleaning the directories.
+///
+/// An empty string has no directories, instead of a single empty one.
+pub fn split_raw_path(s: &OsStr) -> Vec<PathBuf> {
+    match (s.is_empty(), foreign_target()) {
+        (true, _) => vec![],
+        (false, Some(t)) => t
+            .split(&s.to_string_lossy())
+            .into_iter()
+            .map(PathBuf::from)
+            .collect(),
+        (false, None) => split_paths(s).collect(),
     }
 }
 
@@ -26,16 +88,70 @@ pub fn read_path() -> Vec<PathBuf> {
 ///
 /// This is a helper function for a few different others.
 pub fn split_path_like(s: &OsStr) -> Vec<PathBuf> {
-    split_paths(s)
-        .into_iter()
-        .map(|p| clean_dir_name(&p))
-        .collect()
+    split_raw_path(s).iter().map(|p| clean_entry(p)).collect()
 }
 
 /// Combine a multiple directories back into a single `$PATH`-like `OsString`.
 pub fn combine_path_like(dirs: Vec<PathBuf>) -> io::Result<OsString> {
+    if let Some(t) = foreign_target() {
+        let dirs: Vec<Cow<str>> = dirs.iter().map(|d| d.to_string_lossy()).collect();
+        return t.join(&dirs).map(OsString::from);
+    }
+
     match join_paths(dirs) {
         Ok(p) => Ok(p),
-        Err(e) => Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+        Err(e) => Err(PadError::InvalidPath(e.to_string()).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn process_path_from_environ() {
+        use crate::system::{memory::MemorySystem, with_system};
+
+        let sys = MemorySystem::new()
+            .with_contents("/proc/42/environ", "HOME=/home/me\0PATH=/a:/b\0")
+            .with_contents("/proc/43/environ", "HOME=/home/me\0");
+        with_system(sys, || {
+            assert_eq!(read_process_path(42).unwrap(), "/a:/b");
+            assert_eq!(read_process_path(43).unwrap(), "");
+            let err = PadError::from(read_process_path(44).unwrap_err());
+            assert_eq!(err.exit_code(), 14);
+        });
+    }
+
+    #[test]
+    fn empty_path_has_no_dirs() {
+        assert!(split_raw_path(OsStr::new("")).is_empty());
+        assert!(split_path_like(OsStr::new("")).is_empty());
+        assert_eq!(combine_path_like(vec![]).unwrap(), OsString::new());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn empty_entries_kept() {
+        let dirs = split_path_like(OsStr::new(":/pad-path/a:."));
+        assert_eq!(dirs, crate::path::dirs(&["", "/pad-path/a", "."]));
+        assert_eq!(
+            combine_path_like(dirs).unwrap(),
+            OsString::from(":/pad-path/a:.")
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn add_to_empty_path() {
+        use crate::path::add::add_dirs;
+
+        let dirs = add_dirs(
+            split_path_like(OsStr::new("")),
+            vec![PathBuf::from("/a")],
+            false,
+        );
+        assert_eq!(combine_path_like(dirs).unwrap(), OsString::from("/a"));
     }
 }
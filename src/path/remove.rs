@@ -1,10 +1,12 @@
 //! Remove a directory or multiple directories from the `$PATH`.
 
+use super::matching::{find_dir, resolve_dir};
 use super::read::{combine_path_like, read_path};
-use super::write::replace_path;
+use super::write::{replace_path, WriteOpt};
+use crate::config::config;
 use clap::{crate_authors, Parser};
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Parser)]
 #[clap(
@@ -17,55 +19,30 @@ pub struct RmOpt {
     #[clap(default_value = ".")]
     dir: PathBuf,
 
-    /// Don't print warnings when modifying `$PATH`.
-    #[clap(short, long)]
-    quiet: bool,
+    /// Match `DIR` by part of its name if it isn't in `$PATH`
+    #[clap(long)]
+    fuzzy: bool,
 
-    /// Add current `$PATH` to the history
-    #[clap(short = 'H', long)]
-    history: bool,
-
-    /// Don't do anything, just preview what this command would do
-    #[clap(short = 'n', long = "dry-run")]
-    dry_run: bool,
+    #[clap(flatten)]
+    pub write: WriteOpt,
 }
 
 impl RmOpt {
+    /// Whether to match the directory by part of its name.
+    fn fuzzy(&self) -> bool {
+        self.fuzzy || config().fuzzy
+    }
+
     /// Validate options
     pub fn validate(&self) -> io::Result<()> {
-        // check if directory exists
-        if !self.dir.exists() {
-            let err_nonexistent = io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!(
-                    "Directory `{}`  does not exist. `Please double check the directory you intend to remove.",
-                    self.dir.display()
-                )
-            );
-
-            if !self.quiet {
-                eprintln!("{}", err_nonexistent);
-            }
-
-            return Err(err_nonexistent);
-        }
-
-        // check the directory to remove exists in `$PATH`
-        let current_path = read_path();
-        if !current_path.iter().any(|x| *x == self.dir) {
-            let err_not_found = io::Error::new(
-                io::ErrorKind::NotFound,
-                format!(
-                    "Directory `{}` not found in `$PATH`. No changes made.",
-                    self.dir.display()
-                ),
-            );
-
-            if !self.quiet {
-                eprintln!("{}", err_not_found);
+        // check the directory to remove is in `$PATH`, which it may be even if
+        // it no longer exists
+        if let Err(e) = resolve_dir(&read_path(), &self.dir, self.fuzzy()) {
+            if !self.write.quiet {
+                eprintln!("{}", e);
             }
 
-            return Err(err_not_found);
+            return Err(e);
         }
 
         Ok(())
@@ -74,16 +51,15 @@ impl RmOpt {
 
 /// Remove the given directory to the `$PATH` environment variable
 pub fn rm_from_path(opts: &RmOpt) -> io::Result<()> {
-    let current_path = read_path();
-    let i = current_path.iter().position(|x| *x == opts.dir).unwrap();
-
-    let mut vpath = current_path;
-    vpath.remove(i);
-    let newpath = combine_path_like(vpath)?;
-    match replace_path(newpath, opts.dry_run, opts.history, opts.quiet) {
+    let mut vpath = read_path();
+    let result = resolve_dir(&vpath, &opts.dir, opts.fuzzy()).and_then(|i| {
+        vpath.remove(i);
+        replace_path(combine_path_like(vpath)?, &opts.write)
+    });
+    match result {
         Ok(()) => Ok(()),
         Err(e) => {
-            if !opts.quiet {
+            if !opts.write.quiet {
                 eprintln!("{}", e);
             }
 
@@ -91,3 +67,13 @@ pub fn rm_from_path(opts: &RmOpt) -> io::Result<()> {
         }
     }
 }
+
+/// Remove the first occurrence of a directory from a list of directories.
+///
+/// The directory is matched the same way as any other argument, with [`find_dir`].
+pub fn remove_dir(mut dirs: Vec<PathBuf>, dir: &Path) -> Vec<PathBuf> {
+    if let Some(i) = find_dir(&dirs, dir) {
+        dirs.remove(i);
+    }
+    dirs
+}
@@ -1,6 +1,10 @@
 //! Revert `$PATH` to a previous value.
 
-use super::{history::get_nth_last_revision, write::replace_path};
+use super::{
+    history::get_nth_last_revision,
+    write::{replace_path, WriteOpt},
+};
+use crate::error::PadError;
 use clap::{crate_authors, Parser};
 use std::io;
 
@@ -16,22 +20,26 @@ pub struct RevertOpt {
     #[clap(default_value = "1")]
     revision: u128,
 
-    /// Don't print warnings when modifying `$PATH`.
-    #[clap(short, long)]
-    quiet: bool,
-
-    /// Add current `$PATH` to the history
-    #[clap(short = 'H', long)]
-    history: bool,
-
-    /// Don't do anything, just preview what this command would do
-    #[clap(short = 'n', long = "dry-run")]
-    dry_run: bool,
+    #[clap(flatten)]
+    pub write: WriteOpt,
 }
 
 impl RevertOpt {
     /// Validate options
     pub fn validate(&self) -> io::Result<()> {
+        if self.revision == 0 {
+            let err_zero: io::Error = PadError::InvalidInput(String::from(
+                "Revision numbers start at 1, for the most recent version. No changes made.",
+            ))
+            .into();
+
+            if !self.write.quiet {
+                eprintln!("{}", err_zero);
+            }
+
+            return Err(err_zero);
+        }
+
         Ok(())
     }
 }
@@ -44,7 +52,7 @@ pub fn revert_path(opts: &RevertOpt) -> io::Result<()> {
     let newpath = match get_nth_last_revision(opts.revision) {
         Ok(s) => s,
         Err(e) => {
-            if !opts.quiet {
+            if !opts.write.quiet {
                 eprintln!("{}", e);
             }
 
@@ -53,10 +61,10 @@ pub fn revert_path(opts: &RevertOpt) -> io::Result<()> {
     };
 
     // replace the current path with the revised one
-    match replace_path(newpath, opts.dry_run, opts.history, opts.quiet) {
+    match replace_path(newpath, &opts.write) {
         Ok(()) => Ok(()),
         Err(e) => {
-            if !opts.quiet {
+            if !opts.write.quiet {
                 eprintln!("Could not revert `$PATH`. {}. No changes made.", e);
             }
 
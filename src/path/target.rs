@@ -0,0 +1,231 @@
+//! Conventions for `$PATH` on each platform, applied purely to strings.
+//!
+//! This makes it possible to work with a Windows `Path` on Linux, or the other
+//! way around, since nothing depends on the platform `pad` is running on.
+
+use crate::error::PadError;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::io;
+
+/// The platform whose conventions a `$PATH` follows.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Target {
+    /// Directories separated by `:`, with `/` between folders and case-sensitive names
+    Unix,
+    /// Directories separated by `;`, with `\` or `/` between folders and case-insensitive names
+    Windows,
+}
+
+impl Default for Target {
+    fn default() -> Self {
+        Target::host()
+    }
+}
+
+impl Target {
+    /// The platform `pad` is running on.
+    pub fn host() -> Target {
+        match cfg!(windows) {
+            true => Target::Windows,
+            false => Target::Unix,
+        }
+    }
+
+    /// The character between directories in `$PATH`.
+    pub fn separator(&self) -> char {
+        match self {
+            Target::Unix => ':',
+            Target::Windows => ';',
+        }
+    }
+
+    /// The characters between folders in a directory name, with the preferred one first.
+    pub fn dir_separators(&self) -> &'static [char] {
+        match self {
+            Target::Unix => &['/'],
+            Target::Windows => &['\\', '/'],
+        }
+    }
+
+    /// Split a `$PATH` into its directories.
+    ///
+    /// An empty `$PATH` has no directories. On Windows, double quotes around
+    /// part of a directory let it contain `;`.
+    pub fn split(&self, path: &str) -> Vec<String> {
+        if path.is_empty() {
+            return vec![];
+        }
+
+        match self {
+            Target::Unix => path.split(':').map(String::from).collect(),
+            Target::Windows => {
+                let mut dirs = vec![];
+                let mut current = String::new();
+                let mut quoted = false;
+                for c in path.chars() {
+                    match c {
+                        '"' => quoted = !quoted,
+                        ';' if !quoted => dirs.push(std::mem::take(&mut current)),
+                        c => current.push(c),
+                    }
+                }
+                dirs.push(current);
+                dirs
+            }
+        }
+    }
+
+    /// Join directories into a `$PATH`.
+    ///
+    /// Fails if a directory can't be represented, like a Unix directory containing `:`.
+    pub fn join<S: AsRef<str>>(&self, dirs: &[S]) -> io::Result<String> {
+        let sep = self.separator();
+        let mut joined = vec![];
+        for d in dirs.iter().map(|d| d.as_ref()) {
+            match (self, d.contains(sep), d.contains('"')) {
+                (Target::Unix, true, _) => {
+                    return Err(PadError::InvalidPath(format!(
+                        "Directory `{}` contains `{}`, which separates directories.",
+                        d, sep
+                    ))
+                    .into())
+                }
+                (Target::Windows, _, true) => {
+                    return Err(PadError::InvalidPath(format!(
+                        "Directory `{}` contains `\"`, which Windows does not allow.",
+                        d
+                    ))
+                    .into())
+                }
+                (Target::Windows, true, _) => joined.push(format!("\"{}\"", d)),
+                _ => joined.push(String::from(d)),
+            }
+        }
+        Ok(joined.join(&sep.to_string()))
+    }
+
+    /// Whether a directory name is absolute, instead of depending on the current directory.
+    ///
+    /// On Windows, this needs a drive followed by a separator, like `C:\`, or a
+    /// network share, like `\\server\share`.
+    pub fn is_absolute(&self, dir: &str) -> bool {
+        match self {
+            Target::Unix => dir.starts_with('/'),
+            Target::Windows => {
+                dir.starts_with(r"\\")
+                    || dir.starts_with("//")
+                    || (dir.get(..2).is_some_and(is_drive)
+                        && dir[2..].starts_with(self.dir_separators()))
+            }
+        }
+    }
+
+    /// Whether a directory name ends with a folder separator.
+    pub fn has_trailing_separator(&self, dir: &str) -> bool {
+        dir.ends_with(self.dir_separators())
+    }
+
+    /// Remove folder separators from the end of a directory name.
+    ///
+    /// Roots like `/` and `C:\` keep their separator, since removing it would
+    /// change which directory they refer to.
+    pub fn trim_trailing_separators<'a>(&self, dir: &'a str) -> &'a str {
+        let trimmed = dir.trim_end_matches(self.dir_separators());
+        let is_root = match self {
+            Target::Unix => trimmed.is_empty(),
+            Target::Windows => trimmed.is_empty() || is_drive(trimmed),
+        };
+        match is_root && trimmed.len() < dir.len() {
+            true => &dir[..(trimmed.len() + 1)],
+            false => trimmed,
+        }
+    }
+
+    /// Write a directory name in its standard form.
+    ///
+    /// Trailing separators are removed and, on Windows, drive letters are
+    /// capitalized and `/` is replaced by `\`.
+    pub fn normalize(&self, dir: &str) -> String {
+        match self {
+            Target::Unix => String::from(self.trim_trailing_separators(dir)),
+            Target::Windows => {
+                let dir = dir.replace('/', "\\");
+                let dir = self.trim_trailing_separators(&dir);
+                match dir.get(..2) {
+                    Some(drive) if is_drive(drive) => {
+                        format!("{}{}", drive.to_ascii_uppercase(), &dir[2..])
+                    }
+                    _ => String::from(dir),
+                }
+            }
+        }
+    }
+
+    /// Whether two directory names refer to the same directory, without looking at the file system.
+    ///
+    /// Names are compared case-insensitively on Windows.
+    pub fn same_dir(&self, a: &str, b: &str) -> bool {
+        let (a, b) = (self.normalize(a), self.normalize(b));
+        match self {
+            Target::Unix => a == b,
+            Target::Windows => a.to_lowercase() == b.to_lowercase(),
+        }
+    }
+}
+
+/// Whether a string is a Windows drive, like `C:`.
+fn is_drive(s: &str) -> bool {
+    let b = s.as_bytes();
+    b.len() == 2 && b[0].is_ascii_alphabetic() && b[1] == b':'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_and_join_windows() {
+        let path = r#"C:\Windows;"C:\a;b";;D:\Tools"#;
+        let dirs = Target::Windows.split(path);
+        assert_eq!(dirs, vec![r"C:\Windows", r"C:\a;b", "", r"D:\Tools"]);
+        assert_eq!(Target::Windows.join(&dirs).unwrap(), path);
+        assert!(Target::Unix.join(&["/a:b"]).is_err());
+        assert!(Target::Unix.split("").is_empty());
+    }
+
+    #[test]
+    fn trailing_separators_keep_roots() {
+        assert_eq!(
+            Target::Unix.trim_trailing_separators("/usr/bin//"),
+            "/usr/bin"
+        );
+        assert_eq!(Target::Unix.trim_trailing_separators("//"), "/");
+        assert_eq!(
+            Target::Windows.trim_trailing_separators(r"C:\Tools\/"),
+            r"C:\Tools"
+        );
+        assert_eq!(Target::Windows.trim_trailing_separators(r"C:\"), r"C:\");
+        assert!(Target::Windows.has_trailing_separator("C:/Tools/"));
+        assert!(!Target::Unix.has_trailing_separator(r"C:\Tools\"));
+    }
+
+    #[test]
+    fn windows_names_compared_loosely() {
+        assert_eq!(Target::Windows.normalize("c:/Tools/bin/"), r"C:\Tools\bin");
+        assert!(Target::Windows.same_dir(r"C:\TOOLS\bin", "c:/tools/BIN/"));
+        assert!(!Target::Unix.same_dir("/usr/BIN", "/usr/bin"));
+    }
+
+    #[test]
+    fn absolute_dirs() {
+        assert!(Target::Windows.is_absolute(r"C:\Tools"));
+        assert!(Target::Windows.is_absolute(r"\\server\share"));
+        assert!(!Target::Windows.is_absolute(r"C:Tools"));
+        assert!(!Target::Windows.is_absolute(r"\Tools"));
+        assert!(!Target::Windows.is_absolute("/usr/bin"));
+        assert!(Target::Unix.is_absolute("/usr/bin"));
+        assert!(!Target::Unix.is_absolute(r"C:\Tools"));
+    }
+}
@@ -1,41 +1,114 @@
 //! Write out the modified `$PATH`.
 
-use crate::path::{
-    clean::clean_given_path,
-    history::write_to_history,
-    read::{read_raw_path, split_path_like},
+use crate::{
+    config::config,
+    error::PadError,
+    path::{
+        clean::clean_given_path,
+        diff::PathDiff,
+        history::{trim_history, write_to_history},
+        matching::find_dir,
+        read::{read_raw_path, split_path_like},
+    },
+    shell::Shell,
+    system::{stdout, system},
 };
+use clap::Args;
 use std::ffi::OsString;
 use std::io;
 
+/// Options shared by every subcommand that modifies `$PATH`.
+#[derive(Debug, Args)]
+pub struct WriteOpt {
+    /// Don't print warnings when modifying `$PATH`.
+    #[clap(short, long)]
+    pub quiet: bool,
+
+    /// Add current `$PATH` to the history
+    #[clap(short = 'H', long)]
+    pub history: bool,
+
+    /// Don't add current `$PATH` to the history, even if the configuration says to
+    #[clap(long, conflicts_with = "history")]
+    pub no_history: bool,
+
+    /// Don't do anything, just preview what this command would do
+    #[clap(short = 'n', long = "dry-run")]
+    pub dry_run: bool,
+
+    /// Shell syntax to print the modified `$PATH` in
+    #[clap(long, value_enum)]
+    pub shell: Option<Shell>,
+}
+
+impl WriteOpt {
+    /// Whether the current `$PATH` should be added to the history.
+    pub fn add_to_history(&self) -> bool {
+        self.history || (config().history && !self.no_history)
+    }
+
+    /// The shell syntax to print the modified `$PATH` in.
+    pub fn shell(&self) -> Shell {
+        self.shell.unwrap_or(config().shell)
+    }
+}
+
 /// Replace the `$PATH` environment variable.
-pub fn replace_path(
-    newpath: OsString,
-    dry_run: bool,
-    add_to_history: bool,
-    quiet: bool,
-) -> io::Result<()> {
-    let current_raw_path = read_raw_path().unwrap();
-    let current_path = String::from(current_raw_path.to_str().unwrap());
+pub fn replace_path(newpath: OsString, opts: &WriteOpt) -> io::Result<()> {
+    let current_raw_path = read_raw_path().unwrap_or_default();
 
     // clean the newpath before printing it
-    let cleaned_newpath = String::from(
-        clean_given_path(split_path_like(&newpath))?
-            .to_str()
-            .unwrap(),
-    );
-
-    if dry_run && !quiet {
-        eprintln!("`$PATH` before modification:\n\t{}", &current_path);
-        eprintln!("`$PATH` after modification:\n\t{}", &cleaned_newpath);
+    let cleaned_newpath = clean_given_path(split_path_like(&newpath))?;
+    check_protected(&current_raw_path, &cleaned_newpath)?;
+
+    // a dry run only previews the changes, so nothing is printed to STDOUT
+    if opts.dry_run {
+        if opts.quiet {
+            return Ok(());
+        }
+
+        eprintln!(
+            "`$PATH` before modification:\n\t{}",
+            current_raw_path.to_string_lossy()
+        );
+        eprintln!(
+            "`$PATH` after modification:\n\t{}",
+            cleaned_newpath.to_string_lossy()
+        );
+        let diff = PathDiff::new(
+            split_path_like(&current_raw_path),
+            split_path_like(&cleaned_newpath),
+        );
+        let summary = diff.summary();
+        if !summary.is_empty() {
+            eprintln!("Changes:\n\t{}", summary.join("\n\t"));
+        }
         // skip the remainder of the function
         return Ok(());
     }
     // if specified, write the old `$PATH` into the history
-    if add_to_history {
+    if opts.add_to_history() {
         write_to_history(&current_raw_path)?;
+        if let Some(limit) = config().history_limit {
+            trim_history(limit)?;
+        }
+    }
+    opts.shell().write_set_path(&mut stdout(), &cleaned_newpath)
+}
+
+/// Ensure that no protected directories are removed from `$PATH`.
+fn check_protected(current: &OsString, new: &OsString) -> io::Result<()> {
+    let current_dirs = split_path_like(current);
+    let new_dirs = split_path_like(new);
+
+    for d in &config().protected {
+        // directories that don't exist are always removed by cleaning
+        if let Some(i) = find_dir(&current_dirs, d) {
+            if system().exists(&current_dirs[i]) && find_dir(&new_dirs, d).is_none() {
+                return Err(PadError::Protected(current_dirs[i].clone()).into());
+            }
+        }
     }
-    println!("{}", &cleaned_newpath);
 
     Ok(())
 }
@@ -0,0 +1,363 @@
+//! Apply `.padrc` files when changing directory.
+//!
+//! The shell integration calls `pad hook <SHELL>` whenever the working directory
+//! changes, and evaluates the statements it prints.
+//! The `.padrc` file that is currently applied, and the directories it added,
+//! are stored in the `$PAD_PATH_HOOK` environment variable so that they can be
+//! removed again when leaving the directory.
+
+use super::{quote_fish, quote_posix, Shell};
+use crate::{
+    error::PadError,
+    manifest::Manifest,
+    padrc::{find_padrc, is_trusted},
+    path::{
+        add::add_dirs,
+        read::{combine_path_like, read_raw_path, split_raw_path},
+    },
+    system::{stdout, system},
+};
+use clap::{crate_authors, Parser};
+use itertools::Itertools;
+use std::{
+    env::current_exe,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+/// Environment variable holding the state of the directory hook.
+pub const HOOK_ENV_VAR: &str = "PAD_PATH_HOOK";
+
+#[derive(Debug, Parser)]
+#[clap(
+    about = "Apply `.padrc` files when changing directory",
+    author = crate_authors!(),
+)]
+pub struct HookOpt {
+    /// Shell to print statements for
+    #[clap(value_enum)]
+    shell: Shell,
+
+    /// Print the code to add to your shell's startup file
+    #[clap(long)]
+    init: bool,
+
+    /// Don't print warnings.
+    #[clap(short, long)]
+    quiet: bool,
+}
+
+impl HookOpt {
+    /// Validate options
+    pub fn validate(&self) -> io::Result<()> {
+        let supported = match self.init {
+            true => matches!(self.shell, Shell::Bash | Shell::Zsh | Shell::Fish),
+            false => self.shell != Shell::Raw,
+        };
+
+        if !supported {
+            let err_unsupported: io::Error = PadError::Unsupported(format!(
+                "The directory hook is not supported for `{:?}`.",
+                self.shell
+            ))
+            .into();
+
+            if !self.quiet {
+                eprintln!("{}", err_unsupported);
+            }
+
+            return Err(err_unsupported);
+        }
+
+        Ok(())
+    }
+}
+
+/// Print the statements that update `$PATH` for the current directory.
+pub fn run_hook(opts: &HookOpt) -> io::Result<()> {
+    if opts.init {
+        return writeln!(stdout(), "{}", init_script(opts.shell));
+    }
+
+    // anything printed to STDOUT is evaluated by the shell, so errors can't be
+    // passed back up to print the original `$PATH`
+    match hook_statements(opts) {
+        Ok(statements) => {
+            let mut out = stdout();
+            for s in statements {
+                out.write_all(&s)?;
+                out.write_all(b"\n")?;
+            }
+        }
+        Err(e) => {
+            if !opts.quiet {
+                eprintln!("pad hook: {}", e);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Work out which statements need to be evaluated after changing directory.
+///
+/// They are kept as bytes, so that directories that aren't valid UTF-8 stay
+/// exactly as they are every time `$PATH` is set again.
+fn hook_statements(opts: &HookOpt) -> io::Result<Vec<Vec<u8>>> {
+    // what was applied the last time the hook was run
+    let state = split_raw_path(&system().var_os(HOOK_ENV_VAR).unwrap_or_default());
+    let (active, added) = match state.split_first() {
+        Some((active, added)) => (Some(active.clone()), added.to_vec()),
+        None => (None, vec![]),
+    };
+
+    let padrc = match find_padrc(&system().current_dir()?) {
+        Some(p) if is_trusted(&p)? => Some(p),
+        Some(p) => {
+            if !opts.quiet {
+                eprintln!(
+                    "pad hook: `{}` is not trusted. Run `pad allow` to apply it.",
+                    p.display()
+                );
+            }
+            None
+        }
+        None => None,
+    };
+
+    // nothing to do if still within the same project
+    if padrc == active {
+        return Ok(vec![]);
+    }
+
+    // work with the directories exactly as they are in `$PATH`, so that only
+    // the directories added by the hook are touched
+    let mut vpath = split_raw_path(&read_raw_path().unwrap_or_default());
+    for d in &added {
+        if let Some(i) = vpath.iter().position(|x| x == d) {
+            vpath.remove(i);
+        }
+    }
+
+    let mut statements = vec![];
+    match padrc {
+        Some(padrc) => {
+            let root = padrc.parent().unwrap_or(&padrc);
+            let (prepended, appended) = Manifest::from_file(&padrc)?.entries(&vpath, root)?;
+
+            // directories already in `$PATH` are left where they are
+            let prepended: Vec<PathBuf> = prepended
+                .into_iter()
+                .filter(|d| !vpath.contains(d))
+                .unique()
+                .collect();
+            let appended: Vec<PathBuf> = appended
+                .into_iter()
+                .filter(|d| !vpath.contains(d) && !prepended.contains(d))
+                .unique()
+                .collect();
+
+            let mut new_state = vec![padrc.clone()];
+            new_state.extend(prepended.iter().cloned());
+            new_state.extend(appended.iter().cloned());
+
+            let vpath = add_dirs(add_dirs(vpath, prepended, true), appended, false);
+            statements.push(opts.shell.set_path_exact(&combine_path_like(vpath)?));
+            statements.push(
+                opts.shell
+                    .set_var_exact(HOOK_ENV_VAR, &combine_path_like(new_state)?),
+            );
+        }
+        None => {
+            statements.push(opts.shell.set_path_exact(&combine_path_like(vpath)?));
+            statements.push(opts.shell.unset_var(HOOK_ENV_VAR).into_bytes());
+        }
+    }
+
+    Ok(statements)
+}
+
+/// Code to add to a shell's startup file to run the hook when changing directory.
+fn init_script(shell: Shell) -> String {
+    // call this binary directly, since the hook may remove it from `$PATH`
+    let exe = current_exe().unwrap_or_else(|_| PathBuf::from("pad"));
+    let exe = exe.to_string_lossy();
+
+    match shell {
+        Shell::Fish => format!(
+            r#"function _pad_hook --on-variable PWD
+    {exe} hook fish | source
+end
+_pad_hook"#,
+            exe = quote_fish(&exe)
+        ),
+        Shell::Zsh => format!(
+            r#"_pad_hook() {{
+    eval "$({exe} hook zsh)"
+}}
+autoload -Uz add-zsh-hook
+add-zsh-hook chpwd _pad_hook
+_pad_hook"#,
+            exe = quote_posix(&exe)
+        ),
+        _ => format!(
+            r#"_pad_hook() {{
+    if [[ "${{_PAD_PATH_PWD:-}}" != "$PWD" ]]; then
+        _PAD_PATH_PWD="$PWD"
+        eval "$({exe} hook bash)"
+    fi
+}}
+if [[ ";${{PROMPT_COMMAND:-}};" != *";_pad_hook;"* ]]; then
+    PROMPT_COMMAND="_pad_hook${{PROMPT_COMMAND:+;$PROMPT_COMMAND}}"
+fi"#,
+            exe = quote_posix(&exe)
+        ),
+    }
+}
+
+#[cfg(test)]
+#[cfg(unix)]
+mod tests {
+    use super::*;
+    use crate::{
+        padrc::{allow_padrc, TrustOpt},
+        system::{memory::MemorySystem, with_system, Environment},
+    };
+    use std::{
+        ffi::{OsStr, OsString},
+        os::unix::ffi::OsStrExt,
+    };
+
+    /// A home directory, and two projects whose `.padrc` files add their own `bin`.
+    fn projects() -> MemorySystem {
+        let padrc = "[[group]]\nentries = [{ path = \"bin\" }]\n";
+        MemorySystem::new()
+            .with_home("/home/me")
+            .with_path("/usr/bin")
+            .with_dir("/a/bin")
+            .with_dir("/a/src")
+            .with_dir("/b/bin")
+            .with_contents("/a/.padrc", padrc)
+            .with_contents("/b/.padrc", padrc)
+    }
+
+    /// Trust a `.padrc` file.
+    fn allow(sys: &MemorySystem, dir: &str) {
+        let opts = TrustOpt::parse_from(["allow", dir, "--quiet"]);
+        with_system(sys.clone(), || allow_padrc(&opts)).unwrap();
+    }
+
+    /// Change directory and run the hook, then set the variables it printed
+    /// like the shell would.
+    fn cd(sys: &MemorySystem, dir: &str) -> Vec<String> {
+        let opts = HookOpt::parse_from(["hook", "bash", "--quiet"]);
+        let sys = sys.clone().with_cwd(dir);
+        let statements = with_system(sys.clone(), || hook_statements(&opts)).unwrap();
+
+        for s in &statements {
+            if let Some(assignment) = s.strip_prefix(b"export ") {
+                let (name, value) =
+                    assignment.split_at(assignment.iter().position(|b| *b == b'=').unwrap());
+                let value = value[1..]
+                    .strip_prefix(b"'")
+                    .unwrap()
+                    .strip_suffix(b"'")
+                    .unwrap();
+                sys.clone()
+                    .with_var(&String::from_utf8_lossy(name), OsStr::from_bytes(value));
+            }
+            if let Some(name) = s.strip_prefix(b"unset ") {
+                sys.clone().with_var(&String::from_utf8_lossy(name), "");
+            }
+        }
+        statements
+            .iter()
+            .map(|s| String::from_utf8_lossy(s).into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn entering_and_leaving_a_project() {
+        let sys = projects();
+        allow(&sys, "/a");
+
+        assert_eq!(
+            cd(&sys, "/a"),
+            vec![
+                "export PATH='/a/bin:/usr/bin'",
+                "export PAD_PATH_HOOK='/a/.padrc:/a/bin'"
+            ]
+        );
+        // still in the same project
+        assert!(cd(&sys, "/a/src").is_empty());
+        assert_eq!(
+            cd(&sys, "/home/me"),
+            vec!["export PATH='/usr/bin'", "unset PAD_PATH_HOOK"]
+        );
+        assert!(cd(&sys, "/").is_empty());
+    }
+
+    #[test]
+    fn switching_between_projects() {
+        let sys = projects();
+        allow(&sys, "/a");
+        allow(&sys, "/b");
+
+        cd(&sys, "/a/src");
+        assert_eq!(
+            cd(&sys, "/b"),
+            vec![
+                "export PATH='/b/bin:/usr/bin'",
+                "export PAD_PATH_HOOK='/b/.padrc:/b/bin'"
+            ]
+        );
+        // an existing directory is left where it is, and not removed on leaving
+        sys.clone().with_path("/usr/bin:/a/bin");
+        assert_eq!(
+            cd(&sys, "/a"),
+            vec![
+                "export PATH='/usr/bin:/a/bin'",
+                "export PAD_PATH_HOOK='/a/.padrc'"
+            ]
+        );
+        assert_eq!(cd(&sys, "/")[0], "export PATH='/usr/bin:/a/bin'");
+    }
+
+    #[test]
+    fn non_utf8_dirs_kept_exactly() {
+        let sys = projects().with_var("PATH", OsStr::from_bytes(b"/usr/bin:/opt/\xff"));
+        allow(&sys, "/a");
+
+        cd(&sys, "/a");
+        assert_eq!(
+            sys.var_os("PATH").unwrap_or_default(),
+            OsStr::from_bytes(b"/a/bin:/usr/bin:/opt/\xff")
+        );
+        cd(&sys, "/");
+        assert_eq!(
+            sys.var_os("PATH").unwrap_or_default(),
+            OsStr::from_bytes(b"/usr/bin:/opt/\xff")
+        );
+    }
+
+    #[test]
+    fn untrusted_padrc_is_not_applied() {
+        let sys = projects();
+        assert!(cd(&sys, "/a").is_empty());
+
+        allow(&sys, "/a");
+        cd(&sys, "/a");
+        assert_eq!(
+            sys.var_os("PATH").unwrap_or_default(),
+            OsString::from("/a/bin:/usr/bin")
+        );
+
+        // modifying the file revokes trust, so its directories are removed
+        sys.clone()
+            .with_contents("/a/.padrc", "[[group]]\nentries = [{ path = \"src\" }]\n");
+        assert_eq!(
+            cd(&sys, "/a/src"),
+            vec!["export PATH='/usr/bin'", "unset PAD_PATH_HOOK"]
+        );
+        assert!(cd(&sys, "/a").is_empty());
+    }
+}
@@ -0,0 +1,401 @@
+//! Import `$PATH` assignments from existing shell startup files.
+//!
+//! The files are parsed statically, without running them, so only the most
+//! common ways of modifying `$PATH` are understood. Anything else that mentions
+//! `$PATH` is reported so that it can be moved over by hand.
+
+use super::{persist::managed_block, Shell};
+use crate::{
+    config::config,
+    error::PadError,
+    manifest::{Entry, Group, Manifest, Position},
+    path::batch::split_words,
+    system::{stdout, system},
+};
+use clap::{crate_authors, Parser, ValueEnum};
+use itertools::Itertools;
+use regex::Regex;
+use std::{
+    io::{self, Write},
+    path::PathBuf,
+};
+
+/// What to produce from the imported `$PATH` assignments.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ImportFormat {
+    /// A manifest for `pad apply`
+    #[default]
+    Manifest,
+    /// A block for a shell's startup file, like the one written by `pad persist`
+    Block,
+}
+
+#[derive(Debug, Parser)]
+#[clap(
+    about = "Import `$PATH` assignments from shell startup files",
+    author = crate_authors!(),
+)]
+pub struct ImportOpt {
+    /// Startup file to read, like `~/.bashrc` or `~/.config/fish/config.fish`
+    #[clap(long, value_name = "FILE", required = true)]
+    from: Vec<PathBuf>,
+
+    /// What to produce
+    #[clap(short, long, value_enum, default_value_t)]
+    format: ImportFormat,
+
+    /// Shell syntax for `--format block`. Defaults to the `shell` setting.
+    #[clap(long, value_enum)]
+    shell: Option<Shell>,
+
+    /// Don't report lines that could not be understood.
+    #[clap(short, long)]
+    quiet: bool,
+}
+
+impl ImportOpt {
+    /// Validate options
+    pub fn validate(&self) -> io::Result<()> {
+        let mut result = Ok(());
+        if let Some(f) = self.from.iter().find(|f| !system().is_file(f)) {
+            result = Err(
+                PadError::MissingFile(format!("File `{}` does not exist.", f.display())).into(),
+            );
+        } else if self.format == ImportFormat::Block && self.shell() == Shell::Raw {
+            result = Err(PadError::InvalidInput(String::from(
+                "Please choose which shell the block is for with `--shell`.",
+            ))
+            .into());
+        }
+
+        if let Err(e) = &result {
+            if !self.quiet {
+                eprintln!("{}", e);
+            }
+        }
+        result
+    }
+
+    /// The shell syntax for the block.
+    fn shell(&self) -> Shell {
+        self.shell.unwrap_or(config().shell)
+    }
+}
+
+/// Directories added by a startup file, relative to the `$PATH` that existed before it.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Imported {
+    /// Directories placed before the existing `$PATH`, in order of priority
+    prepend: Vec<String>,
+    /// Directories placed after the existing `$PATH`, in order of priority
+    append: Vec<String>,
+    /// Whether the existing `$PATH` was discarded instead of added to
+    replaces: bool,
+}
+
+impl Imported {
+    /// Apply a later assignment on top of the earlier ones.
+    fn extend(&mut self, later: Imported) {
+        self.prepend = later
+            .prepend
+            .into_iter()
+            .chain(self.prepend.drain(..))
+            .collect();
+        self.append.extend(later.append);
+    }
+}
+
+/// Check if a line mentions `$PATH` in any way.
+fn mentions_path(line: &str) -> bool {
+    let re = Regex::new(r"\b(PATH|fish_add_path|fish_user_paths)\b").unwrap();
+    re.is_match(line)
+}
+
+/// Convert a directory from shell syntax into the syntax used by manifests.
+fn convert_dir(dir: &str) -> Result<String, String> {
+    if dir.contains("$(") || dir.contains('`') {
+        return Err(String::from("Command substitution is not supported."));
+    }
+
+    // `$name` becomes `${name}`
+    let simple_var = Regex::new(r"\$([A-Za-z_][A-Za-z0-9_]*)").unwrap();
+    let converted = simple_var.replace_all(dir, "$${${1}}").to_string();
+
+    // anything else starting with `$`, like `${name:-default}`, can't be expanded later
+    let any_var = Regex::new(r"\$\{[A-Za-z_][A-Za-z0-9_]*\}").unwrap();
+    if any_var.replace_all(&converted, "").contains('$') {
+        return Err(format!("Could not understand the expansion in `{}`.", dir));
+    }
+    Ok(converted)
+}
+
+/// Split the values assigned to `$PATH` around references to its previous value.
+fn split_around(values: &[String], previous: &[&str]) -> Result<Imported, String> {
+    let mut imported = Imported::default();
+    let mut after_previous = false;
+    for v in values.iter().flat_map(|v| v.split(':')) {
+        if previous.contains(&v) {
+            after_previous = true;
+        } else if !v.is_empty() {
+            let dir = convert_dir(v)?;
+            match after_previous {
+                true => imported.append.push(dir),
+                false => imported.prepend.push(dir),
+            }
+        }
+    }
+    imported.replaces = !after_previous;
+    Ok(imported)
+}
+
+/// Parse a POSIX shell assignment, like `export PATH="$HOME/bin:$PATH"`.
+fn parse_posix(words: &[String]) -> Result<Imported, String> {
+    let words = match words.first().map(|w| w.as_str()) {
+        Some("export") => &words[1..],
+        _ => words,
+    };
+    let (assignment, rest) = match words.split_first() {
+        Some((a, rest)) => (a, rest.join(" ")),
+        None => return Err(String::from("Expected an assignment to `PATH`.")),
+    };
+
+    let value = match assignment.strip_prefix("PATH=") {
+        Some(value) => Some(value.trim_end_matches(';')),
+        None if assignment == "PATH" => None,
+        None => return Err(String::from("Expected an assignment to `PATH`.")),
+    };
+
+    // `PATH=...; export PATH` is the traditional way of exporting it
+    let rest = rest.trim_start_matches(';').trim();
+    if !rest.is_empty() && rest != "export PATH" {
+        return Err(String::from(
+            "Only a single assignment per line is supported.",
+        ));
+    }
+
+    match value {
+        Some(v) => split_around(&[v.to_string()], &["$PATH", "${PATH}"]),
+        // `export PATH` on its own doesn't change anything
+        None => Ok(Imported::default()),
+    }
+}
+
+/// Parse a fish statement, like `fish_add_path ~/bin` or `set -gx PATH ~/bin $PATH`.
+fn parse_fish(words: &[String]) -> Result<Imported, String> {
+    let (flags, args): (Vec<&String>, Vec<&String>) =
+        words[1..].iter().partition(|w| w.starts_with('-'));
+
+    match words[0].as_str() {
+        "fish_add_path" => {
+            let dirs = args
+                .iter()
+                .map(|d| convert_dir(d))
+                .collect::<Result<Vec<String>, String>>()?;
+            match flags.iter().any(|f| *f == "-a" || *f == "--append") {
+                true => Ok(Imported {
+                    append: dirs,
+                    ..Imported::default()
+                }),
+                false => Ok(Imported {
+                    prepend: dirs,
+                    ..Imported::default()
+                }),
+            }
+        }
+        _ => {
+            // short flags can be combined, like `-gx`
+            let has_flag = |short: char, long: &str| {
+                flags
+                    .iter()
+                    .any(|f| *f == long || !f.starts_with("--") && f.contains(short))
+            };
+            if has_flag('e', "--erase") {
+                return Err(String::from("Only adding to `PATH` is supported."));
+            }
+
+            let values: Vec<String> = match args.split_first() {
+                Some((name, values)) if *name == "PATH" || *name == "fish_user_paths" => {
+                    values.iter().map(|v| v.to_string()).collect()
+                }
+                _ => return Err(String::from("Expected an assignment to `PATH`.")),
+            };
+            let mut imported = split_around(&values, &["$PATH", "$fish_user_paths"])?;
+
+            // `set --append PATH ...` and `set --prepend PATH ...` keep the previous value
+            if has_flag('a', "--append") {
+                imported.append = imported.prepend.drain(..).collect();
+                imported.replaces = false;
+            } else if has_flag('p', "--prepend") {
+                imported.replaces = false;
+            }
+            Ok(imported)
+        }
+    }
+}
+
+/// Parse a line from a startup file.
+///
+/// Returns `None` if the line doesn't modify `$PATH`.
+fn parse_line(line: &str) -> Option<Result<Imported, String>> {
+    let line = line.trim();
+    if line.starts_with('#') || !mentions_path(line) {
+        return None;
+    }
+
+    let words: Vec<String> = match split_words(line) {
+        Ok(w) => w.into_iter().take_while(|w| !w.starts_with('#')).collect(),
+        Err(e) => return Some(Err(e.to_string())),
+    };
+    match words.first().map(|w| w.as_str()) {
+        Some("fish_add_path") | Some("set") => Some(parse_fish(&words)),
+        _ => Some(parse_posix(&words)),
+    }
+}
+
+/// Build a manifest holding the imported directories.
+fn to_manifest(imported: &Imported) -> Manifest {
+    let group = |name: &str, position: Position, dirs: &[String]| Group {
+        name: Some(String::from(name)),
+        position,
+        entries: dirs
+            .iter()
+            .map(|d| Entry {
+                path: d.clone(),
+                ..Entry::default()
+            })
+            .collect(),
+        ..Group::default()
+    };
+
+    let mut groups = vec![];
+    if !imported.prepend.is_empty() {
+        groups.push(group("imported", Position::Prepend, &imported.prepend));
+    }
+    if !imported.append.is_empty() {
+        groups.push(group("imported-append", Position::Append, &imported.append));
+    }
+    Manifest {
+        groups,
+        ..Manifest::default()
+    }
+}
+
+/// Build a managed block holding the imported directories.
+fn to_block(imported: &Imported, shell: Shell) -> io::Result<String> {
+    // the block is evaluated by the shell as-is, so variables are expanded now
+    let expand = |dirs: &[String]| -> io::Result<Vec<PathBuf>> {
+        dirs.iter()
+            .map(|d| Manifest::default().expand(d).map(PathBuf::from))
+            .collect()
+    };
+
+    let mut statements = vec![];
+    if !imported.prepend.is_empty() {
+        statements.push(shell.extend_path(&expand(&imported.prepend)?, true));
+    }
+    if !imported.append.is_empty() {
+        statements.push(shell.extend_path(&expand(&imported.append)?, false));
+    }
+    // the statements are all strings, so the block is valid UTF-8
+    Ok(String::from_utf8_lossy(&managed_block(&statements)).into_owned())
+}
+
+/// Print a manifest or managed block built from the `$PATH` assignments in startup files.
+pub fn import_path(opts: &ImportOpt) -> io::Result<()> {
+    let mut imported = Imported::default();
+    for file in &opts.from {
+        let contents = system().read_to_string(file)?;
+        for (n, line) in contents.lines().enumerate() {
+            let warn = |msg: &str| {
+                if !opts.quiet {
+                    eprintln!("{}:{}: `{}`. {}", file.display(), n + 1, line.trim(), msg);
+                }
+            };
+            match parse_line(line) {
+                Some(Ok(i)) => {
+                    if i.replaces {
+                        warn("This replaces `$PATH` instead of adding to it. Its directories were imported as higher priority than `$PATH`.");
+                    }
+                    imported.extend(i);
+                }
+                Some(Err(e)) => warn(&format!("Could not understand this line. {}", e)),
+                None => {}
+            }
+        }
+    }
+    imported.prepend = imported.prepend.into_iter().unique().collect();
+    imported.append = imported.append.into_iter().unique().collect();
+
+    let output = match opts.format {
+        ImportFormat::Manifest => toml::to_string(&to_manifest(&imported))
+            .map(|m| {
+                let sources = opts
+                    .from
+                    .iter()
+                    .map(|f| format!("# Imported from `{}`\n", f.display()));
+                format!("{}\n{}", sources.collect::<String>(), m)
+            })
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string())),
+        ImportFormat::Block => to_block(&imported, opts.shell()),
+    };
+    match output {
+        Ok(o) => writeln!(stdout(), "{}", o.trim_end()),
+        Err(e) => {
+            if !opts.quiet {
+                eprintln!("{}", e);
+            }
+            Err(e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn posix_assignments() {
+        let imported = parse_line(r#"export PATH="$HOME/bin:$PATH:/opt/bin""#)
+            .unwrap()
+            .unwrap();
+        assert_eq!(imported.prepend, ["${HOME}/bin"]);
+        assert_eq!(imported.append, ["/opt/bin"]);
+
+        let imported = parse_line("PATH=~/bin:${PATH}; export PATH")
+            .unwrap()
+            .unwrap();
+        assert_eq!(imported.prepend, ["~/bin"]);
+        assert!(!imported.replaces);
+
+        assert!(parse_line("alias ll='ls -l'").is_none());
+        assert!(parse_line(r#"PATH="$(brew --prefix)/bin:$PATH""#)
+            .unwrap()
+            .is_err());
+    }
+
+    #[test]
+    fn fish_assignments() {
+        let imported = parse_line("fish_add_path -a /opt/bin ~/bin")
+            .unwrap()
+            .unwrap();
+        assert_eq!(imported.append, ["/opt/bin", "~/bin"]);
+
+        let imported = parse_line("set -gx PATH $HOME/.cargo/bin $PATH")
+            .unwrap()
+            .unwrap();
+        assert_eq!(imported.prepend, ["${HOME}/.cargo/bin"]);
+        let imported = parse_line("set --append PATH /opt/bin").unwrap().unwrap();
+        assert_eq!(imported.append, ["/opt/bin"]);
+        assert!(parse_line("set -e PATH").unwrap().is_err());
+    }
+
+    #[test]
+    fn later_assignments_take_priority() {
+        let mut imported = Imported::default();
+        for line in ["PATH=/a:$PATH", "PATH=/b:$PATH:/c", "PATH=$PATH:/d"] {
+            imported.extend(parse_line(line).unwrap().unwrap());
+        }
+        assert_eq!(imported.prepend, ["/b", "/a"]);
+        assert_eq!(imported.append, ["/c", "/d"]);
+    }
+}
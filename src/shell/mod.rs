@@ -0,0 +1,235 @@
+//! Format a `$PATH` for the shell that will consume it.
+
+pub mod hook;
+pub mod import;
+pub mod persist;
+
+use crate::{path::read::split_raw_path, system::system};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::{
+    ffi::OsStr,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+/// The shell syntax used when printing a modified `$PATH`.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Shell {
+    /// Print the bare value of `$PATH`
+    #[default]
+    Raw,
+    /// POSIX `sh` compatible `export` statement
+    Sh,
+    /// Bash `export` statement
+    Bash,
+    /// Zsh `export` statement
+    Zsh,
+    /// Fish `set -gx` statement
+    Fish,
+    /// PowerShell `$Env:Path` assignment
+    Powershell,
+}
+
+impl Shell {
+    /// Format the value of `$PATH` as a statement that sets it in this shell.
+    pub fn set_path(&self, path: &OsStr) -> String {
+        match self {
+            Shell::Fish => {
+                // fish stores `$PATH` as a list, so each directory is its own argument
+                let dirs: Vec<String> = split_raw_path(path)
+                    .iter()
+                    .map(|p| quote_fish(&p.to_string_lossy()))
+                    .collect();
+                format!("set -gx PATH {}", dirs.join(" "))
+            }
+            Shell::Powershell => self.set_var("Path", path),
+            _ => self.set_var("PATH", path),
+        }
+    }
+
+    /// Format the statement that sets `$PATH` in this shell, like [`Shell::set_path`].
+    ///
+    /// The bare value and POSIX shells' single quotes can hold any bytes, so
+    /// directories that aren't valid UTF-8 are kept exactly.
+    pub fn set_path_exact(&self, path: &OsStr) -> Vec<u8> {
+        match self {
+            Shell::Raw | Shell::Sh | Shell::Bash | Shell::Zsh => self.set_var_exact("PATH", path),
+            _ => self.set_path(path).into_bytes(),
+        }
+    }
+
+    /// Write the statement that sets `$PATH` in this shell, keeping it exactly
+    /// where the shell can.
+    pub fn write_set_path<W: Write>(&self, w: &mut W, path: &OsStr) -> io::Result<()> {
+        w.write_all(&self.set_path_exact(path))?;
+        w.write_all(b"\n")
+    }
+
+    /// Format a statement that sets an environment variable in this shell, like
+    /// [`Shell::set_var`], but keeping the value exactly where the shell can.
+    pub fn set_var_exact(&self, name: &str, value: &OsStr) -> Vec<u8> {
+        let bytes = value.as_encoded_bytes();
+        match self {
+            Shell::Raw => bytes.to_vec(),
+            Shell::Sh | Shell::Bash | Shell::Zsh => {
+                // same as `quote_posix`, but without converting to a `String`
+                let quoted = bytes
+                    .split(|b| *b == b'\'')
+                    .collect::<Vec<_>>()
+                    .join(&b"'\\''"[..]);
+                let mut statement = format!("export {}='", name).into_bytes();
+                statement.extend(quoted);
+                statement.push(b'\'');
+                statement
+            }
+            _ => self.set_var(name, value).into_bytes(),
+        }
+    }
+
+    /// Format a statement that sets an environment variable in this shell.
+    pub fn set_var(&self, name: &str, value: &OsStr) -> String {
+        let value = value.to_string_lossy();
+        match self {
+            Shell::Raw => value.to_string(),
+            Shell::Sh | Shell::Bash | Shell::Zsh => {
+                format!("export {}={}", name, quote_posix(&value))
+            }
+            Shell::Fish => format!("set -gx {} {}", name, quote_fish(&value)),
+            Shell::Powershell => format!("$Env:{} = {}", name, quote_powershell(&value)),
+        }
+    }
+
+    /// Format a statement that adds directories to the beginning or end of `$PATH` in this shell.
+    pub fn extend_path(&self, dirs: &[PathBuf], prepend: bool) -> String {
+        let dirs: Vec<String> = dirs.iter().map(|d| d.to_string_lossy().into()).collect();
+        match self {
+            Shell::Raw => dirs.join(&system().target().separator().to_string()),
+            Shell::Sh | Shell::Bash | Shell::Zsh => {
+                let dirs = quote_posix(&dirs.join(":"));
+                match prepend {
+                    true => format!("export PATH={}:\"$PATH\"", dirs),
+                    false => format!("export PATH=\"$PATH\":{}", dirs),
+                }
+            }
+            Shell::Fish => {
+                let dirs: Vec<String> = dirs.iter().map(|d| quote_fish(d)).collect();
+                match prepend {
+                    true => format!("set -gx PATH {} $PATH", dirs.join(" ")),
+                    false => format!("set -gx PATH $PATH {}", dirs.join(" ")),
+                }
+            }
+            Shell::Powershell => {
+                let dirs = quote_powershell(&dirs.join(";"));
+                match prepend {
+                    true => format!("$Env:Path = {} + ';' + $Env:Path", dirs),
+                    false => format!("$Env:Path = $Env:Path + ';' + {}", dirs),
+                }
+            }
+        }
+    }
+
+    /// Format a statement that removes an environment variable in this shell.
+    pub fn unset_var(&self, name: &str) -> String {
+        match self {
+            Shell::Raw => String::new(),
+            Shell::Sh | Shell::Bash | Shell::Zsh => format!("unset {}", name),
+            Shell::Fish => format!("set -e {}", name),
+            Shell::Powershell => format!("Remove-Item Env:{} -ErrorAction SilentlyContinue", name),
+        }
+    }
+}
+
+/// Quote a string for POSIX shells using single quotes.
+pub fn quote_posix(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+/// Quote a string for the fish shell using single quotes.
+pub fn quote_fish(s: &str) -> String {
+    format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// Quote a string for PowerShell using single quotes.
+pub fn quote_powershell(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsString;
+
+    #[test]
+    fn raw_is_unchanged() {
+        let path = OsString::from("/usr/bin");
+        assert_eq!(Shell::Raw.set_path(&path), "/usr/bin");
+    }
+
+    #[test]
+    fn posix_quotes_single_quotes() {
+        let path = OsString::from("/it's/bin");
+        assert_eq!(Shell::Bash.set_path(&path), "export PATH='/it'\\''s/bin'");
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn fish_splits_into_list() {
+        let path = OsString::from("/usr/bin:/bin");
+        assert_eq!(
+            Shell::Fish.set_path(&path),
+            "set -gx PATH '/usr/bin' '/bin'"
+        );
+    }
+
+    #[test]
+    fn extend_path_keeps_existing_path() {
+        let dirs = vec![PathBuf::from("/a"), PathBuf::from("/b")];
+        assert_eq!(
+            Shell::Zsh.extend_path(&dirs, true),
+            "export PATH='/a:/b':\"$PATH\""
+        );
+        assert_eq!(
+            Shell::Fish.extend_path(&dirs, false),
+            "set -gx PATH $PATH '/a' '/b'"
+        );
+    }
+
+    #[test]
+    fn raw_extend_path_uses_target_separator() {
+        use crate::{
+            path::target::Target,
+            system::{given::GivenPath, with_system},
+        };
+
+        let dirs = vec![PathBuf::from("C:\\a"), PathBuf::from("D:\\b")];
+        let windows = GivenPath::new(system(), None, Some(Target::Windows));
+        assert_eq!(
+            with_system(windows, || Shell::Raw.extend_path(&dirs, true)),
+            "C:\\a;D:\\b"
+        );
+        let unix = GivenPath::new(system(), None, Some(Target::Unix));
+        assert_eq!(
+            with_system(unix, || Shell::Raw.extend_path(&dirs, true)),
+            "C:\\a:D:\\b"
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn non_utf8_written_exactly() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = OsStr::from_bytes(b"/it's/\xff");
+        let mut out: Vec<u8> = vec![];
+        Shell::Sh.write_set_path(&mut out, path).unwrap();
+        assert_eq!(out, b"export PATH='/it'\\''s/\xff'\n");
+    }
+
+    #[test]
+    fn powershell_doubles_quotes() {
+        let path = OsString::from("C:\\it's");
+        assert_eq!(Shell::Powershell.set_path(&path), "$Env:Path = 'C:\\it''s'");
+    }
+}
@@ -0,0 +1,417 @@
+//! Persist `$PATH` in a shell's startup file.
+//!
+//! The statements are kept in a clearly delimited block, so that running
+//! `pad persist` again replaces the block instead of adding another one.
+
+use super::Shell;
+use crate::{
+    config::config,
+    error::PadError,
+    path::{clean::clean_dirs_names, read::read_raw_path},
+    system::system,
+};
+use clap::{crate_authors, Parser};
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+/// First line of the block managed by `pad persist`.
+const BLOCK_START: &str = "# >>> pad-path >>>";
+
+/// Last line of the block managed by `pad persist`.
+const BLOCK_END: &str = "# <<< pad-path <<<";
+
+#[derive(Debug, Parser)]
+#[clap(
+    about = "Save `$PATH` in your shell's startup file",
+    author = crate_authors!(),
+)]
+pub struct PersistOpt {
+    /// Shell whose startup file is modified. Defaults to the `shell` setting.
+    #[clap(long, value_enum)]
+    shell: Option<Shell>,
+
+    /// Startup file to modify. Defaults to the usual file for the shell.
+    #[clap(long)]
+    file: Option<PathBuf>,
+
+    /// Only add a directory to the beginning of `$PATH`, instead of saving all of `$PATH`
+    #[clap(short, long, value_name = "DIR")]
+    prepend: Vec<PathBuf>,
+
+    /// Only add a directory to the end of `$PATH`, instead of saving all of `$PATH`
+    #[clap(short, long, value_name = "DIR")]
+    append: Vec<PathBuf>,
+
+    /// Remove the block added by `pad persist` from the startup file
+    #[clap(long, conflicts_with_all = &["prepend", "append"])]
+    remove: bool,
+
+    /// Don't print warnings.
+    #[clap(short, long)]
+    quiet: bool,
+}
+
+impl PersistOpt {
+    /// Validate options
+    pub fn validate(&self) -> io::Result<()> {
+        // a bare `$PATH` in a startup file would be run as a command
+        let raw_shell = match !self.remove && self.shell() == Shell::Raw {
+            true => Err(PadError::InvalidInput(String::from(
+                "Please choose which shell's syntax to save `$PATH` in with `--shell`.",
+            ))
+            .into()),
+            false => Ok(()),
+        };
+
+        if let Err(e) = raw_shell.and_then(|_| self.startup_file()) {
+            if !self.quiet {
+                eprintln!("{}", e);
+            }
+
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// The shell whose startup file is modified.
+    fn shell(&self) -> Shell {
+        self.shell.unwrap_or(config().shell)
+    }
+
+    /// The startup file to modify.
+    fn startup_file(&self) -> io::Result<PathBuf> {
+        if let Some(f) = &self.file {
+            return Ok(f.clone());
+        }
+
+        let sys = system();
+        let home = sys.home_dir().unwrap_or_default();
+        match self.shell() {
+            Shell::Raw => Err(PadError::InvalidInput(String::from(
+                "Please choose which shell's startup file to modify with `--shell`.",
+            ))
+            .into()),
+            Shell::Sh => Ok(home.join(".profile")),
+            Shell::Bash => Ok(home.join(".bashrc")),
+            Shell::Zsh => Ok(sys
+                .var_os("ZDOTDIR")
+                .map(PathBuf::from)
+                .unwrap_or(home)
+                .join(".zshrc")),
+            Shell::Fish => Ok(sys
+                .config_dir()
+                .unwrap_or_else(|| home.join(".config"))
+                .join("fish")
+                .join("config.fish")),
+            Shell::Powershell => Err(PadError::InvalidInput(String::from(
+                "Please give the location of your PowerShell profile with `--file`.",
+            ))
+            .into()),
+        }
+    }
+
+    /// The statements that go inside the managed block.
+    fn statements(&self) -> Vec<Vec<u8>> {
+        let shell = self.shell();
+        if self.prepend.is_empty() && self.append.is_empty() {
+            return vec![shell.set_path_exact(&read_raw_path().unwrap_or_default())];
+        }
+
+        let mut statements = vec![];
+        if !self.prepend.is_empty() {
+            statements.push(shell.extend_path(&clean_dirs_names(&self.prepend), true));
+        }
+        if !self.append.is_empty() {
+            statements.push(shell.extend_path(&clean_dirs_names(&self.append), false));
+        }
+        statements.into_iter().map(String::into_bytes).collect()
+    }
+}
+
+/// Surround statements with the lines that delimit the managed block.
+///
+/// The statements are bytes, since a `$PATH` set in them may not be valid UTF-8.
+pub fn managed_block<S: AsRef<[u8]>>(statements: &[S]) -> Vec<u8> {
+    let mut block: Vec<&[u8]> = vec![
+        BLOCK_START.as_bytes(),
+        b"# Added by `pad persist`. Changes made within this block will be overwritten.",
+    ];
+    block.extend(statements.iter().map(|s| s.as_ref()));
+    block.push(BLOCK_END.as_bytes());
+    block.join(&b'\n')
+}
+
+/// Split the contents of a file into lines, the same way as `str::lines`.
+fn lines(contents: &[u8]) -> Vec<&[u8]> {
+    let mut lines: Vec<&[u8]> = contents
+        .split(|b| *b == b'\n')
+        .map(|l| l.strip_suffix(b"\r").unwrap_or(l))
+        .collect();
+    // a final line break doesn't start another line
+    if lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+/// Replace the managed block within the contents of a file, or remove it if `block` is `None`.
+///
+/// If the file doesn't have a managed block yet, it is added to the end.
+/// The contents are bytes, so that nothing outside the block has to be valid UTF-8.
+fn replace_block(contents: &[u8], block: Option<&[u8]>) -> io::Result<Vec<u8>> {
+    let lines = lines(contents);
+    let start = lines
+        .iter()
+        .position(|l| l.trim_ascii_end() == BLOCK_START.as_bytes());
+    let end = lines
+        .iter()
+        .position(|l| l.trim_ascii_end() == BLOCK_END.as_bytes());
+
+    let (before, after) = match (start, end) {
+        (Some(s), Some(e)) if s < e => (&lines[..s], &lines[(e + 1)..]),
+        (None, None) => (&lines[..], &lines[lines.len()..]),
+        _ => {
+            return Err(PadError::InvalidFile(format!(
+                "Could not find where the block added by `pad persist` begins and ends. Please check that `{}` is followed by `{}`. No changes made.",
+                BLOCK_START, BLOCK_END
+            ))
+            .into())
+        }
+    };
+
+    let mut new_lines: Vec<&[u8]> = before.to_vec();
+    match block {
+        Some(b) => {
+            // keep the block separate from whatever comes before it
+            if new_lines.last().is_some_and(|l| !l.trim_ascii().is_empty()) {
+                new_lines.push(b"");
+            }
+            new_lines.push(b);
+        }
+        None => {
+            // also remove the blank line that separated the block
+            if start.is_some() && new_lines.last().is_some_and(|l| l.trim_ascii().is_empty()) {
+                new_lines.pop();
+            }
+        }
+    }
+    new_lines.extend_from_slice(after);
+
+    let mut new_contents = new_lines.join(&b'\n');
+    if !new_contents.is_empty() {
+        new_contents.push(b'\n');
+    }
+    Ok(new_contents)
+}
+
+/// Update a file, keeping a backup of its previous contents.
+///
+/// Returns where the backup was saved, if any.
+fn update_file(file: &Path, contents: &[u8]) -> io::Result<Option<PathBuf>> {
+    let sys = system();
+    let backup = match sys.exists(file) {
+        true => {
+            let mut backup = file.as_os_str().to_owned();
+            backup.push(".pad-path.bak");
+            let backup = PathBuf::from(backup);
+            sys.write(&backup, &sys.read(file)?)?;
+            Some(backup)
+        }
+        false => {
+            if let Some(parent) = file.parent() {
+                sys.create_dir_all(parent)?;
+            }
+            None
+        }
+    };
+
+    sys.write(file, contents)?;
+    Ok(backup)
+}
+
+/// Write `$PATH` into a shell's startup file, or remove it from there.
+pub fn persist_path(opts: &PersistOpt) -> io::Result<()> {
+    let result = opts.startup_file().and_then(|file| {
+        let sys = system();
+        let contents = match sys.exists(&file) {
+            true => sys.read(&file)?,
+            false => vec![],
+        };
+
+        let block = match opts.remove {
+            true => None,
+            false => Some(managed_block(&opts.statements())),
+        };
+        let new_contents = replace_block(&contents, block.as_deref())?;
+
+        if new_contents == contents {
+            if !opts.quiet {
+                eprintln!("`{}` is already up to date.", file.display());
+            }
+            return Ok(());
+        }
+
+        let backup = update_file(&file, &new_contents)?;
+        if !opts.quiet {
+            match backup {
+                Some(b) => eprintln!(
+                    "Updated `{}`. The previous version was saved to `{}`.",
+                    file.display(),
+                    b.display()
+                ),
+                None => eprintln!("Created `{}`.", file.display()),
+            }
+        }
+        Ok(())
+    });
+
+    if let Err(e) = &result {
+        if !opts.quiet {
+            eprintln!("{}", e);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_added_replaced_and_removed() {
+        let replace = |contents: &str, block: Option<&[u8]>| {
+            String::from_utf8(replace_block(contents.as_bytes(), block).unwrap()).unwrap()
+        };
+        let original = "alias ll='ls -l'\n";
+        let first = managed_block(&["export PATH='/a'"]);
+        let second = managed_block(&["export PATH='/b'"]);
+
+        let added = replace(original, Some(&first));
+        assert_eq!(
+            added,
+            format!("{}\n{}\n", original, String::from_utf8_lossy(&first))
+        );
+        // running again with the same block changes nothing
+        assert_eq!(replace(&added, Some(&first)), added);
+
+        let replaced = replace(&added, Some(&second));
+        assert!(replaced.contains("'/b'") && !replaced.contains("'/a'"));
+
+        assert_eq!(replace(&replaced, None), original);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn default_startup_files() {
+        use crate::system::{memory::MemorySystem, with_system};
+
+        let opts = |shell| PersistOpt {
+            shell: Some(shell),
+            file: None,
+            prepend: vec![],
+            append: vec![],
+            remove: false,
+            quiet: true,
+        };
+        let sys = MemorySystem::new().with_home("/home/me");
+
+        with_system(sys.clone(), || {
+            assert_eq!(
+                opts(Shell::Bash).startup_file().unwrap(),
+                PathBuf::from("/home/me/.bashrc")
+            );
+            assert_eq!(
+                opts(Shell::Fish).startup_file().unwrap(),
+                PathBuf::from("/home/me/.config/fish/config.fish")
+            );
+        });
+        with_system(sys.with_var("ZDOTDIR", "/home/me/zsh"), || {
+            assert_eq!(
+                opts(Shell::Zsh).startup_file().unwrap(),
+                PathBuf::from("/home/me/zsh/.zshrc")
+            );
+        });
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn startup_file_backed_up() {
+        use crate::system::{memory::MemorySystem, with_system};
+
+        let opts = |remove| PersistOpt {
+            shell: Some(Shell::Bash),
+            file: Some(PathBuf::from("/home/me/.bashrc")),
+            prepend: vec![],
+            append: vec![],
+            remove,
+            quiet: true,
+        };
+        let sys = MemorySystem::new()
+            .with_path("/a:/b")
+            .with_contents("/home/me/.bashrc", "alias ll='ls -l'\n");
+
+        with_system(sys.clone(), || persist_path(&opts(false))).unwrap();
+        let persisted = sys.contents("/home/me/.bashrc").unwrap();
+        assert!(persisted.starts_with("alias ll='ls -l'\n\n"));
+        assert!(persisted.contains("export PATH='/a:/b'"));
+        assert_eq!(
+            sys.contents("/home/me/.bashrc.pad-path.bak").unwrap(),
+            "alias ll='ls -l'\n"
+        );
+
+        with_system(sys.clone(), || persist_path(&opts(true))).unwrap();
+        assert_eq!(
+            sys.contents("/home/me/.bashrc").unwrap(),
+            "alias ll='ls -l'\n"
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn non_utf8_path_persisted_exactly() {
+        use crate::system::{memory::MemorySystem, with_system, FileSystem};
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        let opts = PersistOpt {
+            shell: Some(Shell::Bash),
+            file: Some(PathBuf::from("/home/me/.bashrc")),
+            prepend: vec![],
+            append: vec![],
+            remove: false,
+            quiet: true,
+        };
+        let sys = MemorySystem::new()
+            .with_var("PATH", OsStr::from_bytes(b"/a:/opt/\xff"))
+            .with_dir("/home/me");
+
+        with_system(sys.clone(), || persist_path(&opts)).unwrap();
+        let persisted = sys.read(Path::new("/home/me/.bashrc")).unwrap();
+        assert!(lines(&persisted).contains(&&b"export PATH='/a:/opt/\xff'"[..]));
+    }
+
+    #[test]
+    fn raw_shell_rejected() {
+        let opts = |file: Option<&str>, remove| PersistOpt {
+            shell: Some(Shell::Raw),
+            file: file.map(PathBuf::from),
+            prepend: vec![],
+            append: vec![],
+            remove,
+            quiet: true,
+        };
+
+        assert!(opts(None, false).validate().is_err());
+        assert!(opts(Some("/home/me/.bashrc"), false).validate().is_err());
+        // removing the block doesn't write any statements
+        assert!(opts(Some("/home/me/.bashrc"), true).validate().is_ok());
+    }
+
+    #[test]
+    fn unterminated_block_fails() {
+        let contents = format!("{}\nexport PATH='/a'\n", BLOCK_START);
+        assert!(replace_block(contents.as_bytes(), None).is_err());
+    }
+}
@@ -0,0 +1,110 @@
+//! A system whose `$PATH`, and the platform it is written for, are given on
+//! the command line instead of taken from the environment.
+
+use super::{Environment, FileSystem, Metadata, System};
+use crate::path::target::Target;
+use std::{
+    ffi::OsString,
+    io,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+/// Another system, with `$PATH` and its target platform replaced where given.
+///
+/// Everything else is left to the system it wraps.
+#[derive(Clone)]
+pub struct GivenPath {
+    inner: Rc<dyn System>,
+    path: Option<OsString>,
+    target: Option<Target>,
+}
+
+impl GivenPath {
+    pub fn new(inner: Rc<dyn System>, path: Option<OsString>, target: Option<Target>) -> GivenPath {
+        GivenPath {
+            inner,
+            path,
+            target,
+        }
+    }
+}
+
+impl Environment for GivenPath {
+    fn var_os(&self, key: &str) -> Option<OsString> {
+        match (key, &self.path) {
+            ("PATH", Some(p)) => Some(p.clone()),
+            _ => self.inner.var_os(key),
+        }
+    }
+
+    fn current_dir(&self) -> io::Result<PathBuf> {
+        self.inner.current_dir()
+    }
+
+    fn home_dir(&self) -> Option<PathBuf> {
+        self.inner.home_dir()
+    }
+
+    fn config_dir(&self) -> Option<PathBuf> {
+        self.inner.config_dir()
+    }
+
+    fn write_stdout(&self, bytes: &[u8]) -> io::Result<()> {
+        self.inner.write_stdout(bytes)
+    }
+
+    fn target(&self) -> Target {
+        self.target.unwrap_or_else(|| self.inner.target())
+    }
+}
+
+impl FileSystem for GivenPath {
+    fn exists(&self, p: &Path) -> bool {
+        self.inner.exists(p)
+    }
+
+    fn is_dir(&self, p: &Path) -> bool {
+        self.inner.is_dir(p)
+    }
+
+    fn is_file(&self, p: &Path) -> bool {
+        self.inner.is_file(p)
+    }
+
+    fn is_symlink(&self, p: &Path) -> bool {
+        self.inner.is_symlink(p)
+    }
+
+    fn is_executable(&self, p: &Path) -> bool {
+        self.inner.is_executable(p)
+    }
+
+    fn metadata(&self, p: &Path) -> io::Result<Metadata> {
+        self.inner.metadata(p)
+    }
+
+    fn canonicalize(&self, p: &Path) -> io::Result<PathBuf> {
+        self.inner.canonicalize(p)
+    }
+
+    fn read_dir(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        self.inner.read_dir(dir)
+    }
+
+    fn read(&self, file: &Path) -> io::Result<Vec<u8>> {
+        self.inner.read(file)
+    }
+
+    fn write(&self, file: &Path, contents: &[u8]) -> io::Result<()> {
+        self.inner.write(file, contents)
+    }
+
+    fn append(&self, file: &Path, contents: &[u8]) -> io::Result<()> {
+        self.inner.append(file, contents)
+    }
+
+    fn create_dir_all(&self, dir: &Path) -> io::Result<()> {
+        self.inner.create_dir_all(dir)
+    }
+}
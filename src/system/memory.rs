@@ -0,0 +1,397 @@
+//! An in-memory system, for running operations against a fake `$PATH` and file system.
+
+use super::{Environment, FileSystem, Metadata};
+use std::{
+    cell::{RefCell, RefMut},
+    collections::{BTreeMap, BTreeSet, HashMap},
+    ffi::{OsStr, OsString},
+    io,
+    path::{Component, Path, PathBuf},
+    rc::Rc,
+};
+
+/// Symlinks followed before giving up, like the limit on Linux.
+const MAX_SYMLINKS: usize = 40;
+
+/// ID of the user that owns everything in memory.
+const UID: u32 = 1000;
+
+/// A file in memory.
+#[derive(Clone, Debug, Default)]
+struct File {
+    executable: bool,
+    contents: Vec<u8>,
+}
+
+/// Everything in a [`MemorySystem`], which its clones share.
+#[derive(Debug)]
+struct State {
+    vars: HashMap<String, OsString>,
+    cwd: PathBuf,
+    home: Option<PathBuf>,
+    dirs: BTreeSet<PathBuf>,
+    files: BTreeMap<PathBuf, File>,
+    /// Symlinks, and where they point to
+    symlinks: BTreeMap<PathBuf, PathBuf>,
+    stdout: Vec<u8>,
+}
+
+/// A fake environment and file system, built up with the `with_*` methods.
+///
+/// Relative paths are resolved from the current directory, which is `/` unless set.
+/// Clones share the same state, so a test can keep a clone to look at the
+/// files and output that an operation wrote.
+#[derive(Clone, Debug)]
+pub struct MemorySystem {
+    state: Rc<RefCell<State>>,
+}
+
+impl Default for MemorySystem {
+    fn default() -> Self {
+        MemorySystem {
+            state: Rc::new(RefCell::new(State {
+                vars: HashMap::new(),
+                cwd: PathBuf::from("/"),
+                home: None,
+                dirs: BTreeSet::from([PathBuf::from("/")]),
+                files: BTreeMap::new(),
+                symlinks: BTreeMap::new(),
+                stdout: vec![],
+            })),
+        }
+    }
+}
+
+impl MemorySystem {
+    pub fn new() -> MemorySystem {
+        MemorySystem::default()
+    }
+
+    /// Set an environment variable.
+    pub fn with_var(self, key: &str, value: impl AsRef<OsStr>) -> MemorySystem {
+        self.state
+            .borrow_mut()
+            .vars
+            .insert(String::from(key), value.as_ref().to_os_string());
+        self
+    }
+
+    /// Set `$PATH`.
+    pub fn with_path(self, value: &str) -> MemorySystem {
+        self.with_var("PATH", value)
+    }
+
+    /// Set the current directory, creating it if needed.
+    pub fn with_cwd(self, dir: &str) -> MemorySystem {
+        let sys = self.with_dir(dir);
+        sys.state.borrow_mut().cwd = PathBuf::from(dir);
+        sys
+    }
+
+    /// Set the home directory, creating it if needed.
+    pub fn with_home(self, dir: &str) -> MemorySystem {
+        let sys = self.with_dir(dir);
+        sys.state.borrow_mut().home = Some(PathBuf::from(dir));
+        sys
+    }
+
+    /// Create a directory and all of its parents.
+    pub fn with_dir(self, dir: &str) -> MemorySystem {
+        self.add_dirs(&self.absolute(Path::new(dir)));
+        self
+    }
+
+    /// Create an empty file, and the directory it is in.
+    pub fn with_file(self, file: &str, executable: bool) -> MemorySystem {
+        self.add_file(
+            Path::new(file),
+            File {
+                executable,
+                contents: vec![],
+            },
+        );
+        self
+    }
+
+    /// Create a file with some contents, and the directory it is in.
+    pub fn with_contents(self, file: &str, contents: &str) -> MemorySystem {
+        self.add_file(
+            Path::new(file),
+            File {
+                executable: false,
+                contents: contents.as_bytes().to_vec(),
+            },
+        );
+        self
+    }
+
+    /// Create a symlink pointing to `target`, which is relative to the link's directory.
+    pub fn with_symlink(self, link: &str, target: &str) -> MemorySystem {
+        let link = self.absolute(Path::new(link));
+        if let Some(parent) = link.parent() {
+            self.add_dirs(parent);
+        }
+        self.state
+            .borrow_mut()
+            .symlinks
+            .insert(link, PathBuf::from(target));
+        self
+    }
+
+    /// The contents of a file, if it exists and is valid UTF-8.
+    pub fn contents(&self, file: &str) -> Option<String> {
+        let resolved = self.resolve(Path::new(file)).ok()?;
+        let state = self.state.borrow();
+        let file = state.files.get(&resolved)?;
+        String::from_utf8(file.contents.clone()).ok()
+    }
+
+    /// Take everything written to STDOUT so far.
+    pub fn take_stdout(&self) -> String {
+        let stdout = std::mem::take(&mut self.state.borrow_mut().stdout);
+        String::from_utf8_lossy(&stdout).into_owned()
+    }
+
+    /// Add a directory and all of its parents.
+    fn add_dirs(&self, dir: &Path) {
+        self.state
+            .borrow_mut()
+            .dirs
+            .extend(dir.ancestors().map(Path::to_path_buf));
+    }
+
+    /// Add a file, and the directory it is in.
+    fn add_file(&self, file: &Path, contents: File) {
+        let file = self.absolute(file);
+        if let Some(parent) = file.parent() {
+            self.add_dirs(parent);
+        }
+        self.state.borrow_mut().files.insert(file, contents);
+    }
+
+    /// Get a file to write to, creating it if its directory exists.
+    fn open(&self, file: &Path) -> io::Result<RefMut<'_, File>> {
+        let resolved = self.resolve(file)?;
+        let state = self.state.borrow_mut();
+        if !resolved.parent().is_some_and(|p| state.dirs.contains(p)) {
+            return Err(not_found());
+        }
+        Ok(RefMut::map(state, |s| s.files.entry(resolved).or_default()))
+    }
+
+    /// Make a path absolute without resolving symlinks or `..`.
+    fn absolute(&self, p: &Path) -> PathBuf {
+        self.state.borrow().cwd.join(p)
+    }
+
+    /// Resolve every symlink and `.` or `..` in a path, whether or not it exists.
+    fn resolve(&self, p: &Path) -> io::Result<PathBuf> {
+        let state = self.state.borrow();
+        let mut resolved = PathBuf::from("/");
+        let mut pending: Vec<OsString> = vec![];
+        let mut followed = 0;
+
+        // components are processed in order, so they are stored in reverse
+        let push_components = |pending: &mut Vec<OsString>, p: &Path| {
+            let names: Vec<OsString> = p
+                .components()
+                .filter_map(|c| match c {
+                    Component::Normal(n) => Some(n.to_os_string()),
+                    Component::ParentDir => Some(OsString::from("..")),
+                    _ => None,
+                })
+                .collect();
+            pending.extend(names.into_iter().rev());
+        };
+        push_components(&mut pending, &state.cwd.join(p));
+
+        while let Some(name) = pending.pop() {
+            if name == ".." {
+                resolved.pop();
+                continue;
+            }
+
+            let candidate = resolved.join(&name);
+            match state.symlinks.get(&candidate) {
+                Some(target) => {
+                    followed += 1;
+                    if followed > MAX_SYMLINKS {
+                        return Err(io::Error::other("Too many levels of symbolic links"));
+                    }
+                    if target.is_absolute() {
+                        resolved = PathBuf::from("/");
+                    }
+                    push_components(&mut pending, target);
+                }
+                None => resolved = candidate,
+            }
+        }
+
+        Ok(resolved)
+    }
+}
+
+/// Error for a path that doesn't exist.
+fn not_found() -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, "No such file or directory")
+}
+
+impl Environment for MemorySystem {
+    fn var_os(&self, key: &str) -> Option<OsString> {
+        self.state.borrow().vars.get(key).cloned()
+    }
+
+    fn current_dir(&self) -> io::Result<PathBuf> {
+        Ok(self.state.borrow().cwd.clone())
+    }
+
+    fn home_dir(&self) -> Option<PathBuf> {
+        self.state.borrow().home.clone()
+    }
+
+    fn config_dir(&self) -> Option<PathBuf> {
+        self.home_dir().map(|h| h.join(".config"))
+    }
+
+    fn write_stdout(&self, bytes: &[u8]) -> io::Result<()> {
+        self.state.borrow_mut().stdout.extend_from_slice(bytes);
+        Ok(())
+    }
+}
+
+impl FileSystem for MemorySystem {
+    fn exists(&self, p: &Path) -> bool {
+        self.canonicalize(p).is_ok()
+    }
+
+    fn is_dir(&self, p: &Path) -> bool {
+        self.resolve(p)
+            .is_ok_and(|r| self.state.borrow().dirs.contains(&r))
+    }
+
+    fn is_file(&self, p: &Path) -> bool {
+        self.resolve(p)
+            .is_ok_and(|r| self.state.borrow().files.contains_key(&r))
+    }
+
+    fn is_symlink(&self, p: &Path) -> bool {
+        // only the last component may be a symlink, so resolve its parent
+        match (p.parent(), p.file_name()) {
+            (Some(parent), Some(name)) => self
+                .resolve(parent)
+                .is_ok_and(|r| self.state.borrow().symlinks.contains_key(&r.join(name))),
+            _ => false,
+        }
+    }
+
+    fn is_executable(&self, p: &Path) -> bool {
+        self.resolve(p).is_ok_and(|r| {
+            self.state
+                .borrow()
+                .files
+                .get(&r)
+                .is_some_and(|f| f.executable)
+        })
+    }
+
+    fn metadata(&self, p: &Path) -> io::Result<Metadata> {
+        let resolved = self.canonicalize(p)?;
+        let state = self.state.borrow();
+        let mode = match state.files.get(&resolved) {
+            Some(f) if !f.executable => 0o644,
+            _ => 0o755,
+        };
+        Ok(Metadata {
+            is_dir: state.dirs.contains(&resolved),
+            readonly: false,
+            mode: Some(mode),
+            uid: Some(UID),
+        })
+    }
+
+    fn canonicalize(&self, p: &Path) -> io::Result<PathBuf> {
+        let resolved = self.resolve(p)?;
+        let state = self.state.borrow();
+        match state.dirs.contains(&resolved) || state.files.contains_key(&resolved) {
+            true => Ok(resolved),
+            false => Err(not_found()),
+        }
+    }
+
+    fn read_dir(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        let resolved = self.canonicalize(dir)?;
+        let state = self.state.borrow();
+        let children = state
+            .dirs
+            .iter()
+            .chain(state.files.keys())
+            .chain(state.symlinks.keys())
+            .filter(|p| p.parent() == Some(resolved.as_path()))
+            .filter_map(|p| p.file_name())
+            .map(|name| dir.join(name))
+            .collect();
+        Ok(children)
+    }
+
+    fn read(&self, file: &Path) -> io::Result<Vec<u8>> {
+        let resolved = self.resolve(file)?;
+        match self.state.borrow().files.get(&resolved) {
+            Some(f) => Ok(f.contents.clone()),
+            None => Err(not_found()),
+        }
+    }
+
+    fn write(&self, file: &Path, contents: &[u8]) -> io::Result<()> {
+        self.open(file)?.contents = contents.to_vec();
+        Ok(())
+    }
+
+    fn append(&self, file: &Path, contents: &[u8]) -> io::Result<()> {
+        self.open(file)?.contents.extend_from_slice(contents);
+        Ok(())
+    }
+
+    fn create_dir_all(&self, dir: &Path) -> io::Result<()> {
+        let resolved = self.resolve(dir)?;
+        self.add_dirs(&resolved);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn symlinks_are_resolved() {
+        let sys = MemorySystem::new()
+            .with_file("/usr/bin/sh", true)
+            .with_symlink("/bin", "usr/bin")
+            .with_cwd("/home/user");
+
+        assert_eq!(
+            sys.canonicalize(Path::new("../../bin/sh")).unwrap(),
+            PathBuf::from("/usr/bin/sh")
+        );
+        assert!(sys.is_symlink(Path::new("/bin")));
+        assert!(sys.is_executable(Path::new("/bin/sh")));
+        assert!(!sys.exists(Path::new("/bin/bash")));
+        assert_eq!(
+            sys.read_dir(Path::new("/bin")).unwrap(),
+            vec![PathBuf::from("/bin/sh")]
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn files_are_written_through_clones() {
+        let sys = MemorySystem::new().with_dir("/etc");
+        let clone = sys.clone();
+
+        clone.write(Path::new("/etc/a"), b"contents").unwrap();
+        assert!(clone.write(Path::new("/var/a"), b"").is_err());
+        assert_eq!(sys.contents("/etc/a").as_deref(), Some("contents"));
+        assert!(sys.is_file(Path::new("/etc/a")));
+    }
+}
@@ -0,0 +1,266 @@
+//! Access to the environment and file system that `$PATH` is read from.
+//!
+//! Operations go through [`system`] instead of calling `std::env`, `std::fs`,
+//! and `println!` directly, so that tests can run them against an in-memory
+//! system with a fake `$PATH`, directory layout, and STDOUT.
+//! Only the temporary file given to `$EDITOR` uses the real file system, since
+//! the editor is another process that needs to open it.
+
+pub mod given;
+#[cfg(test)]
+pub mod memory;
+
+use crate::path::target::Target;
+use std::{
+    cell::RefCell,
+    env,
+    ffi::OsString,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+/// Environment variables and other state of the running process.
+pub trait Environment {
+    /// Get the value of an environment variable.
+    fn var_os(&self, key: &str) -> Option<OsString>;
+
+    /// Get the current working directory.
+    fn current_dir(&self) -> io::Result<PathBuf>;
+
+    /// Get the user's home directory.
+    fn home_dir(&self) -> Option<PathBuf>;
+
+    /// Get the user's configuration directory, like `$XDG_CONFIG_HOME`.
+    fn config_dir(&self) -> Option<PathBuf>;
+
+    /// Write to STDOUT, where the new `$PATH` and other results are printed.
+    fn write_stdout(&self, bytes: &[u8]) -> io::Result<()>;
+
+    /// The platform whose conventions `$PATH` follows.
+    fn target(&self) -> Target {
+        Target::host()
+    }
+}
+
+/// Details about a path, after following symlinks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Metadata {
+    /// Whether the path is a directory
+    pub is_dir: bool,
+    /// Whether the path can't be written to
+    pub readonly: bool,
+    /// Unix permission bits, like `0o755`
+    pub mode: Option<u32>,
+    /// ID of the user that owns the path, on Unix
+    pub uid: Option<u32>,
+}
+
+/// Queries about the directories and files that `$PATH` refers to.
+pub trait FileSystem {
+    /// Whether a path exists, after following symlinks.
+    fn exists(&self, p: &Path) -> bool;
+
+    /// Whether a path is a directory, after following symlinks.
+    fn is_dir(&self, p: &Path) -> bool;
+
+    /// Whether a path is a file, after following symlinks.
+    fn is_file(&self, p: &Path) -> bool;
+
+    /// Whether a path is itself a symbolic link.
+    fn is_symlink(&self, p: &Path) -> bool;
+
+    /// Whether a path is a file that can be executed.
+    fn is_executable(&self, p: &Path) -> bool;
+
+    /// Get the permissions and owner of a path, which must exist.
+    fn metadata(&self, p: &Path) -> io::Result<Metadata>;
+
+    /// Make a path absolute and resolve all symlinks. The path must exist.
+    fn canonicalize(&self, p: &Path) -> io::Result<PathBuf>;
+
+    /// List the paths directly within a directory, in no particular order.
+    fn read_dir(&self, dir: &Path) -> io::Result<Vec<PathBuf>>;
+
+    /// Read the contents of a file.
+    fn read(&self, file: &Path) -> io::Result<Vec<u8>>;
+
+    /// Read the contents of a file, which must be valid UTF-8.
+    fn read_to_string(&self, file: &Path) -> io::Result<String> {
+        String::from_utf8(self.read(file)?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Create or replace a file. Its directory must already exist.
+    fn write(&self, file: &Path, contents: &[u8]) -> io::Result<()>;
+
+    /// Add to the end of a file, creating it if needed. Its directory must already exist.
+    fn append(&self, file: &Path, contents: &[u8]) -> io::Result<()>;
+
+    /// Create a directory and any of its parents that are missing.
+    fn create_dir_all(&self, dir: &Path) -> io::Result<()>;
+}
+
+/// Everything that operations on `$PATH` need from the outside world.
+pub trait System: Environment + FileSystem {}
+
+impl<T: Environment + FileSystem> System for T {}
+
+/// The actual environment and file system of this process.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RealSystem;
+
+impl Environment for RealSystem {
+    fn var_os(&self, key: &str) -> Option<OsString> {
+        env::var_os(key)
+    }
+
+    fn current_dir(&self) -> io::Result<PathBuf> {
+        env::current_dir()
+    }
+
+    fn home_dir(&self) -> Option<PathBuf> {
+        dirs_next::home_dir()
+    }
+
+    fn config_dir(&self) -> Option<PathBuf> {
+        dirs_next::config_dir()
+    }
+
+    fn write_stdout(&self, bytes: &[u8]) -> io::Result<()> {
+        io::stdout().lock().write_all(bytes)
+    }
+}
+
+impl FileSystem for RealSystem {
+    fn exists(&self, p: &Path) -> bool {
+        p.exists()
+    }
+
+    fn is_dir(&self, p: &Path) -> bool {
+        p.is_dir()
+    }
+
+    fn is_file(&self, p: &Path) -> bool {
+        p.is_file()
+    }
+
+    fn is_symlink(&self, p: &Path) -> bool {
+        p.is_symlink()
+    }
+
+    #[cfg(unix)]
+    fn is_executable(&self, p: &Path) -> bool {
+        use std::os::unix::fs::PermissionsExt;
+
+        match p.metadata() {
+            Ok(md) => md.is_file() && md.permissions().mode() & 0o111 != 0,
+            Err(_) => false,
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn is_executable(&self, p: &Path) -> bool {
+        // Windows determines this by the extension, not by permissions
+        let exts = self
+            .var_os("PATHEXT")
+            .map(|e| e.to_string_lossy().into_owned())
+            .unwrap_or_else(|| String::from(".COM;.EXE;.BAT;.CMD"));
+        let is_exe_ext = match p.extension() {
+            Some(ext) => exts.split(';').any(|e| {
+                e.trim_start_matches('.')
+                    .eq_ignore_ascii_case(&ext.to_string_lossy())
+            }),
+            None => false,
+        };
+        is_exe_ext && p.is_file()
+    }
+
+    fn metadata(&self, p: &Path) -> io::Result<Metadata> {
+        let md = fs::metadata(p)?;
+
+        #[cfg(unix)]
+        let (mode, uid) = {
+            use std::os::unix::fs::{MetadataExt, PermissionsExt};
+            (Some(md.permissions().mode()), Some(md.uid()))
+        };
+        #[cfg(not(unix))]
+        let (mode, uid) = (None, None);
+
+        Ok(Metadata {
+            is_dir: md.is_dir(),
+            readonly: md.permissions().readonly(),
+            mode,
+            uid,
+        })
+    }
+
+    fn canonicalize(&self, p: &Path) -> io::Result<PathBuf> {
+        fs::canonicalize(p)
+    }
+
+    fn read_dir(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        fs::read_dir(dir)?.map(|e| e.map(|e| e.path())).collect()
+    }
+
+    fn read(&self, file: &Path) -> io::Result<Vec<u8>> {
+        fs::read(file)
+    }
+
+    fn write(&self, file: &Path, contents: &[u8]) -> io::Result<()> {
+        fs::write(file, contents)
+    }
+
+    fn append(&self, file: &Path, contents: &[u8]) -> io::Result<()> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(file)?
+            .write_all(contents)
+    }
+
+    fn create_dir_all(&self, dir: &Path) -> io::Result<()> {
+        fs::create_dir_all(dir)
+    }
+}
+
+thread_local! {
+    /// The system that operations run against on this thread.
+    static SYSTEM: RefCell<Rc<dyn System>> = RefCell::new(Rc::new(RealSystem));
+}
+
+/// Get the system that operations run against.
+///
+/// This is the [`RealSystem`], unless a test has replaced it with [`with_system`].
+pub fn system() -> Rc<dyn System> {
+    SYSTEM.with(|s| s.borrow().clone())
+}
+
+/// STDOUT of the system that operations run against.
+#[derive(Clone, Copy, Debug)]
+pub struct Stdout;
+
+impl Write for Stdout {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        system().write_stdout(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Get STDOUT of the system that operations run against, to print results to.
+pub fn stdout() -> Stdout {
+    Stdout
+}
+
+/// Run a function against a different system, on this thread only.
+pub fn with_system<T>(sys: impl System + 'static, f: impl FnOnce() -> T) -> T {
+    let previous = SYSTEM.with(|s| s.replace(Rc::new(sys)));
+    let result = f();
+    SYSTEM.with(|s| s.replace(previous));
+    result
+}
@@ -0,0 +1,384 @@
+//! Interactively edit `$PATH` in the terminal.
+//!
+//! The editor is drawn on STDERR so that the resulting `$PATH` can still be
+//! captured from STDOUT, like every other subcommand.
+
+use crate::{
+    error::PadError,
+    path::{
+        clean::clean_dir_name,
+        list::{inspect_path, PathEntry},
+        read::{combine_path_like, read_raw_path, split_raw_path},
+        write::{replace_path, WriteOpt},
+    },
+};
+use clap::{crate_authors, Parser};
+use ratatui::{
+    backend::CrosstermBackend,
+    crossterm::{
+        event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+        execute,
+        terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    },
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    Frame, Terminal,
+};
+use std::{
+    io::{self, Stderr},
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Parser)]
+#[clap(
+    about = "Interactively edit `$PATH`",
+    author = crate_authors!(),
+)]
+pub struct TuiOpt {
+    #[clap(flatten)]
+    pub write: WriteOpt,
+}
+
+impl TuiOpt {
+    /// Validate options
+    pub fn validate(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A directory being edited.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Item {
+    /// The directory
+    dir: PathBuf,
+    /// Whether the directory is kept in `$PATH`
+    enabled: bool,
+}
+
+/// What keypresses are currently being used for.
+#[derive(Debug, PartialEq, Eq)]
+enum Mode {
+    /// Navigating and rearranging directories
+    Normal,
+    /// Typing the name of a new directory
+    Insert(String),
+}
+
+/// What to do after handling a keypress.
+#[derive(Debug, PartialEq, Eq)]
+enum Action {
+    Continue,
+    Accept,
+    Cancel,
+}
+
+/// State of the interactive editor.
+struct Editor {
+    items: Vec<Item>,
+    cursor: usize,
+    mode: Mode,
+    /// Details about each item, in the same order, for showing warnings
+    entries: Vec<PathEntry>,
+}
+
+impl Editor {
+    fn new(dirs: Vec<PathBuf>) -> Editor {
+        let mut editor = Editor {
+            items: dirs
+                .into_iter()
+                .map(|dir| Item { dir, enabled: true })
+                .collect(),
+            cursor: 0,
+            mode: Mode::Normal,
+            entries: vec![],
+        };
+        editor.refresh();
+        editor
+    }
+
+    /// Recompute the warnings for each item.
+    fn refresh(&mut self) {
+        let dirs: Vec<PathBuf> = self.items.iter().map(|i| i.dir.clone()).collect();
+        self.entries = match combine_path_like(dirs) {
+            Ok(p) => inspect_path(&p, true),
+            Err(_) => vec![],
+        };
+    }
+
+    /// The directories that will be written to `$PATH`.
+    fn result(&self) -> Vec<PathBuf> {
+        self.items
+            .iter()
+            .filter(|i| i.enabled)
+            .map(|i| i.dir.clone())
+            .collect()
+    }
+
+    fn cursor_up(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    fn cursor_down(&mut self) {
+        if self.cursor + 1 < self.items.len() {
+            self.cursor += 1;
+        }
+    }
+
+    /// Swap the selected item with the one before it.
+    fn move_up(&mut self) {
+        if self.cursor > 0 {
+            self.items.swap(self.cursor, self.cursor - 1);
+            self.cursor -= 1;
+            self.refresh();
+        }
+    }
+
+    /// Swap the selected item with the one after it.
+    fn move_down(&mut self) {
+        if self.cursor + 1 < self.items.len() {
+            self.items.swap(self.cursor, self.cursor + 1);
+            self.cursor += 1;
+            self.refresh();
+        }
+    }
+
+    fn delete(&mut self) {
+        if self.cursor < self.items.len() {
+            self.items.remove(self.cursor);
+            self.cursor = self.cursor.min(self.items.len().saturating_sub(1));
+            self.refresh();
+        }
+    }
+
+    fn toggle(&mut self) {
+        if let Some(item) = self.items.get_mut(self.cursor) {
+            item.enabled = !item.enabled;
+        }
+    }
+
+    /// Insert a directory after the selected one.
+    fn insert(&mut self, dir: &Path) {
+        let idx = match self.items.is_empty() {
+            true => 0,
+            false => self.cursor + 1,
+        };
+        self.items.insert(
+            idx,
+            Item {
+                dir: clean_dir_name(dir),
+                enabled: true,
+            },
+        );
+        self.cursor = idx;
+        self.refresh();
+    }
+
+    /// Update the state after a keypress.
+    fn handle_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> Action {
+        if let Mode::Insert(input) = &mut self.mode {
+            match code {
+                KeyCode::Enter => {
+                    let dir = PathBuf::from(input.trim());
+                    self.mode = Mode::Normal;
+                    if !dir.as_os_str().is_empty() {
+                        self.insert(&dir);
+                    }
+                }
+                KeyCode::Esc => self.mode = Mode::Normal,
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Char(c) => input.push(c),
+                _ => {}
+            }
+            return Action::Continue;
+        }
+
+        let shift = modifiers.contains(KeyModifiers::SHIFT);
+        match code {
+            KeyCode::Up if shift => self.move_up(),
+            KeyCode::Down if shift => self.move_down(),
+            KeyCode::Char('K') => self.move_up(),
+            KeyCode::Char('J') => self.move_down(),
+            KeyCode::Up | KeyCode::Char('k') => self.cursor_up(),
+            KeyCode::Down | KeyCode::Char('j') => self.cursor_down(),
+            KeyCode::Char(' ') => self.toggle(),
+            KeyCode::Char('d') | KeyCode::Delete => self.delete(),
+            KeyCode::Char('i') | KeyCode::Char('a') => self.mode = Mode::Insert(String::new()),
+            KeyCode::Enter | KeyCode::Char('w') => return Action::Accept,
+            KeyCode::Esc | KeyCode::Char('q') => return Action::Cancel,
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => {
+                return Action::Cancel
+            }
+            _ => {}
+        }
+        Action::Continue
+    }
+
+    /// Describe any problems with an item.
+    fn warnings(&self, idx: usize) -> Vec<Span<'static>> {
+        let mut warnings = vec![];
+        let entry = match self.entries.get(idx) {
+            Some(e) => e,
+            None => return warnings,
+        };
+
+        if !entry.exists {
+            warnings.push(Span::styled(" missing", Style::default().fg(Color::Red)));
+        }
+        if let Some(i) = entry.duplicate_of {
+            warnings.push(Span::styled(
+                format!(" duplicate of {}", i),
+                Style::default().fg(Color::Yellow),
+            ));
+        }
+        if !entry.shadows.is_empty() {
+            let shadowed: Vec<String> = entry.shadows.iter().map(|i| i.to_string()).collect();
+            warnings.push(Span::styled(
+                format!(" shadows {}", shadowed.join(", ")),
+                Style::default().fg(Color::Magenta),
+            ));
+        }
+        warnings
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [list_area, help_area] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+
+        let items: Vec<ListItem> = self
+            .items
+            .iter()
+            .enumerate()
+            .map(|(idx, item)| {
+                let check = match item.enabled {
+                    true => "[x] ",
+                    false => "[ ] ",
+                };
+                let dir_style = match item.enabled {
+                    true => Style::default(),
+                    false => Style::default().add_modifier(Modifier::DIM | Modifier::CROSSED_OUT),
+                };
+                let mut spans = vec![
+                    Span::styled(
+                        format!("{:>3} ", idx),
+                        Style::default().add_modifier(Modifier::DIM),
+                    ),
+                    Span::raw(check),
+                    Span::styled(item.dir.display().to_string(), dir_style),
+                ];
+                spans.extend(self.warnings(idx));
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(" $PATH "))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let mut state = ListState::default().with_selected(Some(self.cursor));
+        frame.render_stateful_widget(list, list_area, &mut state);
+
+        let help = match &self.mode {
+            Mode::Normal => Paragraph::new(
+                "j/k: select  J/K: move  space: toggle  d: delete  i: insert  enter: accept  q: cancel",
+            ),
+            Mode::Insert(input) => Paragraph::new(format!("New directory: {}", input)),
+        };
+        frame.render_widget(help, help_area);
+    }
+}
+
+/// Run the editor until the user accepts or cancels their changes.
+fn run_editor(
+    terminal: &mut Terminal<CrosstermBackend<Stderr>>,
+    editor: &mut Editor,
+) -> io::Result<Action> {
+    loop {
+        terminal.draw(|f| editor.draw(f))?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match editor.handle_key(key.code, key.modifiers) {
+                Action::Continue => {}
+                action => return Ok(action),
+            }
+        }
+    }
+}
+
+/// Interactively edit `$PATH`.
+pub fn edit_interactively(opts: &TuiOpt) -> io::Result<()> {
+    let dirs = split_raw_path(&read_raw_path().unwrap_or_default());
+    let mut editor = Editor::new(dirs);
+
+    enable_raw_mode()?;
+    let mut stderr = io::stderr();
+    execute!(stderr, EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stderr))?;
+
+    let action = run_editor(&mut terminal, &mut editor);
+
+    // always restore the terminal, even if something went wrong
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+
+    // an empty `$PATH` is almost certainly a mistake, so it is treated like
+    // cancelling, as `pad edit` does
+    match action? {
+        Action::Accept if !editor.result().is_empty() => {
+            let newpath = combine_path_like(editor.result())?;
+            match replace_path(newpath, &opts.write) {
+                Ok(()) => Ok(()),
+                Err(e) => {
+                    if !opts.write.quiet {
+                        eprintln!("{}", e);
+                    }
+                    Err(e)
+                }
+            }
+        }
+        _ => {
+            let err: io::Error = PadError::Cancelled.into();
+            if !opts.write.quiet {
+                eprintln!("{}", err);
+            }
+            Err(err)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::dirs;
+
+    fn editor(names: &[&str]) -> Editor {
+        Editor::new(dirs(names))
+    }
+
+    fn press(e: &mut Editor, keys: &str) {
+        for c in keys.chars() {
+            e.handle_key(KeyCode::Char(c), KeyModifiers::NONE);
+        }
+    }
+
+    #[test]
+    fn move_toggle_and_delete() {
+        let mut e = editor(&["/a", "/b", "/c"]);
+        // select `/c`, move it to the top, then disable `/a` and delete `/b`
+        press(&mut e, "jjKKj jd");
+        assert_eq!(e.result(), vec![PathBuf::from("/c")]);
+    }
+
+    #[test]
+    fn insert_after_cursor() {
+        let mut e = editor(&["/a", "/b"]);
+        press(&mut e, "i/new");
+        e.handle_key(KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(e.result(), dirs(&["/a", "/new", "/b"]));
+    }
+}